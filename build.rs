@@ -75,7 +75,8 @@ fn main() {
     options.set_source_language(shaderc::SourceLanguage::HLSL);
 
     println!("cargo::rerun-if-changed=shaders/basic.hlsl");
-    let hlsl_shaders = ["shaders/basic.hlsl"];
+    println!("cargo::rerun-if-changed=shaders/fullscreen.hlsl");
+    let hlsl_shaders = ["shaders/basic.hlsl", "shaders/fullscreen.hlsl"];
 
     if path::Path::new("assets/spirv").exists() {
         fs::remove_dir_all("assets/spirv").expect("Failed to recreate oupput dir");
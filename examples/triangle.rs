@@ -293,10 +293,10 @@ impl ApplicationHandler for Application {
             );
 
             let device = Rc::new(context
-                .create_graphic_device_default()
+                .create_graphic_device_default(None)
                 .expect("Failed to create device"));
 
-            let swapchain = device.create_swapchain(800, 600);
+            let swapchain = device.create_swapchain(800, 600, true);
 
             self.window = Some(window);
             self.context = Some(context);
@@ -0,0 +1,19 @@
+struct VsOutput
+{
+    float4 position : SV_Position;
+    [[vk::location(0)]] float2 uv : TEXCOORD0;
+};
+
+// Single triangle covering the whole viewport, generated from the vertex index without any vertex buffer
+VsOutput VSMain(uint vertex_id : SV_VertexID) {
+    VsOutput output = (VsOutput)0;
+
+    output.uv = float2((vertex_id << 1) & 2, vertex_id & 2);
+    output.position = float4(output.uv * 2.0 - 1.0, 0.0, 1.0);
+    return output;
+}
+
+float4 PSMain(VsOutput input) : COLOR
+{
+    return float4(input.uv, 0.0, 1.0);
+}
@@ -10,6 +10,7 @@ use std::path::PathBuf;
 #[derive(Debug)]
 pub enum Error {
     Io(io::Error),
+    Image(image::ImageError),
     NulError,
     FailedToGetCurrentDirPath,
     FailedToGetExePath,
@@ -23,6 +24,21 @@ impl From<io::Error> for Error {
     }
 }
 
+impl From<image::ImageError> for Error {
+    fn from(other: image::ImageError) -> Self {
+        Error::Image(other)
+    }
+}
+
+/// Decoded 8 bit per channel pixels, rows are tightly packed from the top left corner
+pub struct ImageData {
+    pub width: u32,
+    pub height: u32,
+    /// 1 for grayscale, 2 for grayscale + alpha and 4 for RGBA
+    pub channels: u8,
+    pub pixels: Vec<u8>,
+}
+
 pub struct AssetManager {
     root_dir: PathBuf,
 }
@@ -83,4 +99,32 @@ impl AssetManager {
         file.read_to_end(&mut buffer)?;
         Ok(buffer)
     }
+
+    /// Decodes a PNG, JPEG or other supported image file
+    ///
+    /// Grayscale images keep their channel count (R8 / RG8 textures), 16 bit ones like
+    /// heightmaps are reduced to 8 bits, everything else is converted to RGBA8. Whether the pixels are sRGB is decided by the upload texture format.
+    pub fn read_image(&self, asset_path: &str, flip_y: bool) -> Result<ImageData, Error> {
+        let buffer = self.read_bytes(asset_path)?;
+        let mut image = image::load_from_memory(&buffer)?;
+        if flip_y {
+            image = image.flipv();
+        }
+
+        let (width, height) = (image.width(), image.height());
+        let (channels, pixels) = match image.color() {
+            image::ColorType::L8 | image::ColorType::L16 => (1, image.into_luma8().into_raw()),
+            image::ColorType::La8 | image::ColorType::La16 => {
+                (2, image.into_luma_alpha8().into_raw())
+            }
+            _ => (4, image.into_rgba8().into_raw()),
+        };
+
+        Ok(ImageData {
+            width,
+            height,
+            channels,
+            pixels,
+        })
+    }
 }
@@ -6,5 +6,14 @@ pub mod splines;
 pub mod primitives;
 pub mod surfaces;
 pub mod algorithms;
+pub mod bounds;
 
-use nalgebra_glm::{Vec2, Vec3};
+use nalgebra_glm::{Vec2, Vec3, Vec4};
+
+#[derive(Debug)]
+pub enum Error {
+    IndexOutOfRange { index: u32, vertex_count: usize },
+    ParseError { line: usize },
+    /// The number of parsed control points is not a whole number of patches
+    IncompletePatch { points: usize },
+}
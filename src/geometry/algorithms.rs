@@ -2,6 +2,8 @@
 * SPDX-License-Identifier: MIT
 */
 
+use nalgebra_glm as glm;
+
 use super::Vec3;
 
 pub fn de_casteljau(t: f32, points: &[Vec3]) -> Vec3 {
@@ -13,3 +15,181 @@ pub fn de_casteljau(t: f32, points: &[Vec3]) -> Vec3 {
     }
     points[0]
 }
+
+/// Right handed perspective projection with a reversed [1, 0] depth range and an infinite far plane
+///
+/// Reversed Z spreads the float depth precision evenly over the view distance, it must be paired
+/// with a depth buffer cleared to 0.0 and a `GREATER` depth compare operation.
+pub fn perspective_reverse_z(aspect: f32, fovy: f32, near: f32) -> glm::Mat4 {
+    glm::reversed_infinite_perspective_rh_zo(aspect, fovy, near)
+}
+
+/// Reorders a triangle list for the post transform vertex cache with the Tipsify algorithm
+/// (Sander, Nehab and Barczak 2007)
+///
+/// Triangles are emitted in fans around vertices that are likely still cached, `cache_size`
+/// is the targeted number of cached vertices, 16 suits most GPUs. The triangles themselves
+/// and their winding are kept, only their order changes. A trailing partial triangle, when
+/// `indices.len()` is not a multiple of 3, is dropped.
+pub fn optimize_indices(indices: &[u32], vertex_count: usize, cache_size: usize) -> Vec<u32> {
+    let triangle_count = indices.len() / 3;
+
+    let mut live_triangles = vec![0usize; vertex_count];
+    for &index in &indices[..triangle_count * 3] {
+        live_triangles[index as usize] += 1;
+    }
+    let mut adjacency: Vec<Vec<usize>> = live_triangles
+        .iter()
+        .map(|&count| Vec::with_capacity(count))
+        .collect();
+    for (triangle, vertices) in indices.chunks_exact(3).enumerate() {
+        for &vertex in vertices {
+            adjacency[vertex as usize].push(triangle);
+        }
+    }
+
+    let mut cache_time = vec![0usize; vertex_count];
+    let mut time = cache_size + 1;
+    let mut emitted = vec![false; triangle_count];
+    let mut dead_ends = Vec::new();
+    let mut cursor = 0;
+    let mut output = Vec::with_capacity(triangle_count * 3);
+
+    let mut fan = (vertex_count > 0).then_some(0);
+    while let Some(vertex) = fan {
+        let mut candidates = Vec::new();
+        for &triangle in &adjacency[vertex] {
+            if emitted[triangle] {
+                continue;
+            }
+            emitted[triangle] = true;
+            for &index in &indices[triangle * 3..triangle * 3 + 3] {
+                let index = index as usize;
+                output.push(index as u32);
+                dead_ends.push(index);
+                candidates.push(index);
+                live_triangles[index] -= 1;
+                if time - cache_time[index] > cache_size {
+                    cache_time[index] = time;
+                    time += 1;
+                }
+            }
+        }
+
+        // Prefer the candidate that stays cached the longest while its remaining fan is drawn
+        fan = None;
+        let mut best_priority = None;
+        for &candidate in &candidates {
+            if live_triangles[candidate] == 0 {
+                continue;
+            }
+            let age = time - cache_time[candidate];
+            let priority = if age + 2 * live_triangles[candidate] <= cache_size {
+                age
+            } else {
+                0
+            };
+            if Some(priority) > best_priority {
+                best_priority = Some(priority);
+                fan = Some(candidate);
+            }
+        }
+
+        if fan.is_none() {
+            fan = std::iter::from_fn(|| dead_ends.pop()).find(|&d| live_triangles[d] > 0);
+        }
+        if fan.is_none() {
+            while cursor < vertex_count && live_triangles[cursor] == 0 {
+                cursor += 1;
+            }
+            fan = (cursor < vertex_count).then_some(cursor);
+        }
+    }
+    output
+}
+
+/// Remaps vertices in the order they are first referenced by `indices` so that vertex fetches
+/// walk memory linearly, returns the new vertices and indices
+///
+/// Vertices never referenced are dropped.
+pub fn optimize_vertex_fetch<T: Copy>(vertices: &[T], indices: &[u32]) -> (Vec<T>, Vec<u32>) {
+    let mut remap = vec![u32::MAX; vertices.len()];
+    let mut remapped_vertices = Vec::with_capacity(vertices.len());
+    let remapped_indices = indices
+        .iter()
+        .map(|&index| {
+            let new_index = &mut remap[index as usize];
+            if *new_index == u32::MAX {
+                *new_index = remapped_vertices.len() as u32;
+                remapped_vertices.push(vertices[index as usize]);
+            }
+            *new_index
+        })
+        .collect();
+    (remapped_vertices, remapped_indices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Triangle list of a `size` by `size` vertex grid whose indices start at `first`
+    fn grid(size: u32, first: u32) -> Vec<u32> {
+        let mut indices = Vec::new();
+        for y in 0..size - 1 {
+            for x in 0..size - 1 {
+                let i = first + y * size + x;
+                indices.extend_from_slice(&[i, i + size, i + 1, i + 1, i + size, i + size + 1]);
+            }
+        }
+        indices
+    }
+
+    fn sorted_triangles(indices: &[u32]) -> Vec<[u32; 3]> {
+        let mut triangles: Vec<[u32; 3]> = indices
+            .chunks_exact(3)
+            .map(|t| [t[0], t[1], t[2]])
+            .collect();
+        triangles.sort();
+        triangles
+    }
+
+    #[test]
+    fn optimize_indices_keeps_triangles() {
+        let indices = grid(5, 0);
+        for cache_size in [0, 1, 2, 3, 16] {
+            let optimized = optimize_indices(&indices, 25, cache_size);
+            assert_eq!(sorted_triangles(&optimized), sorted_triangles(&indices));
+        }
+    }
+
+    #[test]
+    fn optimize_indices_skips_unused_vertices() {
+        // vertices 0, 1 and the last two are not referenced by any triangle
+        let indices = grid(4, 2);
+        let optimized = optimize_indices(&indices, 20, 16);
+        assert_eq!(sorted_triangles(&optimized), sorted_triangles(&indices));
+    }
+
+    #[test]
+    fn optimize_indices_drops_partial_triangle() {
+        let mut indices = grid(3, 0);
+        let triangles = sorted_triangles(&indices);
+        indices.extend_from_slice(&[0, 1]);
+        let optimized = optimize_indices(&indices, 9, 16);
+        assert_eq!(optimized.len(), triangles.len() * 3);
+        assert_eq!(sorted_triangles(&optimized), triangles);
+    }
+
+    #[test]
+    fn optimize_vertex_fetch_remaps_in_first_use_order() {
+        let vertices = ['a', 'b', 'c', 'd', 'e'];
+        let indices = [3, 1, 4, 4, 1, 0];
+        let (remapped_vertices, remapped_indices) = optimize_vertex_fetch(&vertices, &indices);
+        assert_eq!(remapped_vertices, ['d', 'b', 'e', 'a']);
+        assert_eq!(remapped_indices, [0, 1, 2, 2, 1, 3]);
+        for (&old, &new) in indices.iter().zip(&remapped_indices) {
+            assert_eq!(vertices[old as usize], remapped_vertices[new as usize]);
+        }
+    }
+}
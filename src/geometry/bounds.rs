@@ -0,0 +1,154 @@
+/*
+* SPDX-License-Identifier: MIT
+*/
+
+use nalgebra_glm as glm;
+
+use super::Vec3;
+use super::Vec4;
+
+/// Axis aligned bounding box
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    /// Returns the smallest box containing `points`, None when there are no points
+    pub fn from_points<'a>(points: impl IntoIterator<Item = &'a Vec3>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = *points.next()?;
+        Some(points.fold(
+            Self {
+                min: first,
+                max: first,
+            },
+            |aabb, point| Self {
+                min: glm::min2(&aabb.min, point),
+                max: glm::max2(&aabb.max, point),
+            },
+        ))
+    }
+
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn corners(&self) -> [Vec3; 8] {
+        let (min, max) = (self.min, self.max);
+        [
+            Vec3::new(min.x, min.y, min.z),
+            Vec3::new(max.x, min.y, min.z),
+            Vec3::new(min.x, max.y, min.z),
+            Vec3::new(max.x, max.y, min.z),
+            Vec3::new(min.x, min.y, max.z),
+            Vec3::new(max.x, min.y, max.z),
+            Vec3::new(min.x, max.y, max.z),
+            Vec3::new(max.x, max.y, max.z),
+        ]
+    }
+
+    /// Returns the box containing this one once transformed by `model`,
+    /// used to move object space bounds to world space
+    pub fn transformed(&self, model: &glm::Mat4) -> Self {
+        let corners = self
+            .corners()
+            .map(|corner| (model * Vec4::new(corner.x, corner.y, corner.z, 1.0)).xyz());
+        Self::from_points(&corners).unwrap()
+    }
+}
+
+/// The six clip planes of a view projection, as `(normal, distance)` with the inside positive
+#[derive(Clone, Copy, Debug)]
+pub struct Frustum {
+    pub planes: [Vec4; 6],
+}
+
+impl Frustum {
+    /// Extracts the planes of `view_projection` (Gribb and Hartmann)
+    ///
+    /// The Vulkan clip volume `-w <= x, y <= w, 0 <= z <= w` is assumed, which holds for reversed
+    /// Z and infinite projections too, the far plane of the latter never rejects anything.
+    pub fn from_view_projection(view_projection: &glm::Mat4) -> Self {
+        let row = |i: usize| view_projection.row(i).transpose();
+        let (x, y, z, w) = (row(0), row(1), row(2), row(3));
+        Self {
+            planes: [w + x, w - x, w + y, w - y, z, w - z],
+        }
+    }
+
+    /// Conservative test, boxes near the frustum corners may be reported visible
+    pub fn intersects(&self, aabb: &Aabb) -> bool {
+        self.planes.iter().all(|plane| {
+            // the corner furthest along the plane normal
+            let corner = Vec3::new(
+                if plane.x >= 0.0 {
+                    aabb.max.x
+                } else {
+                    aabb.min.x
+                },
+                if plane.y >= 0.0 {
+                    aabb.max.y
+                } else {
+                    aabb.min.y
+                },
+                if plane.z >= 0.0 {
+                    aabb.max.z
+                } else {
+                    aabb.min.z
+                },
+            );
+            glm::dot(&plane.xyz(), &corner) + plane.w >= 0.0
+        })
+    }
+}
+
+/// Returns the objects whose world space bounds, given by `bounds`, intersect `frustum`
+pub fn cull<'a, T>(frustum: &Frustum, objects: &'a [T], bounds: impl Fn(&T) -> Aabb) -> Vec<&'a T> {
+    objects
+        .iter()
+        .filter(|object| frustum.intersects(&bounds(object)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::algorithms;
+
+    fn cube(center: Vec3) -> Aabb {
+        Aabb {
+            min: center - Vec3::repeat(1.0),
+            max: center + Vec3::repeat(1.0),
+        }
+    }
+
+    #[test]
+    fn frustum_intersects_boxes_in_view() {
+        let projection = glm::perspective_rh_zo(1.0, std::f32::consts::FRAC_PI_2, 0.1, 100.0);
+        let frustum = Frustum::from_view_projection(&projection);
+        assert!(frustum.intersects(&cube(Vec3::new(0.0, 0.0, -10.0))));
+        // partly outside the left plane and straddling the near plane
+        assert!(frustum.intersects(&cube(Vec3::new(-10.5, 0.0, -10.0))));
+        assert!(frustum.intersects(&cube(Vec3::new(0.0, 0.0, 0.0))));
+    }
+
+    #[test]
+    fn frustum_rejects_boxes_out_of_view() {
+        let projection = glm::perspective_rh_zo(1.0, std::f32::consts::FRAC_PI_2, 0.1, 100.0);
+        let frustum = Frustum::from_view_projection(&projection);
+        assert!(!frustum.intersects(&cube(Vec3::new(0.0, 0.0, 10.0))));
+        assert!(!frustum.intersects(&cube(Vec3::new(0.0, 0.0, -200.0))));
+        assert!(!frustum.intersects(&cube(Vec3::new(20.0, 0.0, -10.0))));
+        assert!(!frustum.intersects(&cube(Vec3::new(0.0, -20.0, -10.0))));
+    }
+
+    #[test]
+    fn reverse_z_frustum_has_no_far_plane() {
+        let projection = algorithms::perspective_reverse_z(1.0, std::f32::consts::FRAC_PI_2, 0.1);
+        let frustum = Frustum::from_view_projection(&projection);
+        assert!(frustum.intersects(&cube(Vec3::new(0.0, 0.0, -10000.0))));
+        assert!(!frustum.intersects(&cube(Vec3::new(0.0, 0.0, 10.0))));
+    }
+}
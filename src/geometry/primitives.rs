@@ -2,28 +2,74 @@
 * SPDX-License-Identifier: MIT
 */
 
+use std::collections::HashMap;
+
+use nalgebra_glm as glm;
+
+use super::algorithms::{optimize_indices, optimize_vertex_fetch};
+use super::bounds::Aabb;
+use super::Error;
 use super::Vec2;
 use super::Vec3;
+use super::Vec4;
+
+/// Plain old data that can be copied byte for byte into a GPU buffer
+///
+/// # Safety
+///
+/// Implementors must be `#[repr(C)]` (or primitives), contain no padding bytes,
+/// no pointers or references and be valid for any bit pattern read back from the GPU.
+pub unsafe trait VertexData: Copy + 'static {}
+
+/// Reinterprets a slice of vertex data as raw bytes for upload
+pub fn as_bytes<T: VertexData>(data: &[T]) -> &[u8] {
+    unsafe { std::slice::from_raw_parts(data.as_ptr() as *const u8, std::mem::size_of_val(data)) }
+}
 
 #[repr(C)]
+#[derive(Clone, Copy)]
 pub struct Line {
     pub start: Vec3,
     pub end: Vec3,
 }
 
 #[repr(C)]
+#[derive(Clone, Copy)]
 pub struct SimpleVertex {
     pub position: Vec3,
     pub normal: Vec3,
 }
 
 #[repr(C)]
+#[derive(Clone, Copy)]
 pub struct Vertex {
     pub position: Vec3,
     pub normal: Vec3,
     pub uv: Vec2,
 }
 
+/// Vertex with a second texture coordinate set for lightmaps and a RGBA vertex color
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct VertexFull {
+    pub position: Vec3,
+    pub normal: Vec3,
+    pub uv: Vec2,
+    pub uv2: Vec2,
+    pub color: Vec4,
+}
+
+unsafe impl VertexData for f32 {}
+unsafe impl VertexData for u16 {}
+unsafe impl VertexData for u32 {}
+unsafe impl VertexData for Vec2 {}
+unsafe impl VertexData for Vec3 {}
+unsafe impl VertexData for Vec4 {}
+unsafe impl VertexData for Line {}
+unsafe impl VertexData for SimpleVertex {}
+unsafe impl VertexData for Vertex {}
+unsafe impl VertexData for VertexFull {}
+
 pub struct PolyLine {
     pub points: Vec<Vec3>,
     pub line_strip: bool
@@ -37,6 +83,12 @@ impl PolyLine {
         false
     }
 }
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Winding {
+    Clockwise,
+    CounterClockwise,
+}
+
 pub struct SimpleMesh {
     pub vertices: Vec<Vertex>,
 }
@@ -45,3 +97,138 @@ pub struct Mesh {
     pub vertices: Vec<Vertex>,
     pub indices: Vec<u32>,
 }
+
+impl Mesh {
+    /// Checks that every index references an existing vertex
+    ///
+    /// An out of range index makes the GPU read past the end of the vertex buffer,
+    /// this check scans the whole index buffer so it is meant for debug builds.
+    pub fn validate_indices(&self) -> Result<(), Error> {
+        let vertex_count = self.vertices.len();
+        match self.indices.iter().max() {
+            Some(&index) if index as usize >= vertex_count => Err(Error::IndexOutOfRange {
+                index,
+                vertex_count,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Object space bounds of the vertices, None for an empty mesh
+    pub fn bounds(&self) -> Option<Aabb> {
+        Aabb::from_points(self.vertices.iter().map(|vertex| &vertex.position))
+    }
+
+    /// Reorders the triangles for the vertex cache then the vertices for fetch locality
+    ///
+    /// This is an opt-in step for imported meshes, the indices must be valid.
+    pub fn optimize_vertex_cache(&mut self) {
+        let indices = optimize_indices(&self.indices, self.vertices.len(), 16);
+        (self.vertices, self.indices) = optimize_vertex_fetch(&self.vertices, &indices);
+    }
+
+    /// Guesses the winding of the triangles as seen from outside the mesh
+    ///
+    /// The guess comes from the sign of the enclosed volume, it is reliable for closed
+    /// meshes only and returns None for flat or degenerate ones.
+    pub fn dominant_winding(&self) -> Option<Winding> {
+        let signed_volume: f32 = self
+            .indices
+            .chunks_exact(3)
+            .map(|triangle| {
+                let p0 = &self.vertices[triangle[0] as usize].position;
+                let p1 = &self.vertices[triangle[1] as usize].position;
+                let p2 = &self.vertices[triangle[2] as usize].position;
+                glm::dot(p0, &glm::cross(p1, p2))
+            })
+            .sum();
+
+        if signed_volume > f32::EPSILON {
+            Some(Winding::CounterClockwise)
+        } else if signed_volume < -f32::EPSILON {
+            Some(Winding::Clockwise)
+        } else {
+            None
+        }
+    }
+}
+
+/// Generates the two triangle per quad index list (0, 1, 2, 2, 3, 0) for `quad_count` quads
+/// whose four vertices are stored consecutively
+pub fn quad_indices(quad_count: usize) -> Vec<u32> {
+    let mut indices = Vec::with_capacity(quad_count * 6);
+    for quad in 0..quad_count as u32 {
+        let first = quad * 4;
+        indices.extend_from_slice(&[first, first + 1, first + 2, first + 2, first + 3, first]);
+    }
+    indices
+}
+
+/// Expands a triangle list into a triangle list with adjacency (6 indices per triangle)
+/// for `vk::PrimitiveTopology::TRIANGLE_LIST_WITH_ADJACENCY`
+///
+/// Each triangle `(v0, v1, v2)` becomes `(v0, a01, v1, a12, v2, a20)` where `aij` is the
+/// vertex opposite to the edge `vi vj` in the neighbouring triangle. Edges are matched by
+/// position so vertices split for normals or uvs are still connected. On an open edge
+/// the current vertex `vi` is duplicated.
+pub fn build_adjacency_indices(positions: &[Vec3], indices: &[u32]) -> Vec<u32> {
+    let position_key = |index: u32| {
+        let p = &positions[index as usize];
+        [p.x.to_bits(), p.y.to_bits(), p.z.to_bits()]
+    };
+
+    let mut welded = HashMap::new();
+    let canonical: Vec<u32> = indices
+        .iter()
+        .map(|&index| *welded.entry(position_key(index)).or_insert(index))
+        .collect();
+
+    let mut opposite = HashMap::new();
+    for (triangle, welded_triangle) in indices.chunks_exact(3).zip(canonical.chunks_exact(3)) {
+        for edge in 0..3 {
+            let start = welded_triangle[edge];
+            let end = welded_triangle[(edge + 1) % 3];
+            opposite.insert((start, end), triangle[(edge + 2) % 3]);
+        }
+    }
+
+    let mut adjacency = Vec::with_capacity(indices.len() * 2);
+    for (triangle, welded_triangle) in indices.chunks_exact(3).zip(canonical.chunks_exact(3)) {
+        for edge in 0..3 {
+            let start = welded_triangle[edge];
+            let end = welded_triangle[(edge + 1) % 3];
+            adjacency.push(triangle[edge]);
+            adjacency.push(*opposite.get(&(end, start)).unwrap_or(&triangle[edge]));
+        }
+    }
+    adjacency
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quad_positions() -> Vec<Vec3> {
+        vec![
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        ]
+    }
+
+    #[test]
+    fn build_adjacency_indices_links_shared_edge() {
+        let adjacency = build_adjacency_indices(&quad_positions(), &[0, 1, 2, 2, 3, 0]);
+        // the diagonal is shared, the four outer edges are open
+        assert_eq!(adjacency, [0, 0, 1, 1, 2, 3, 2, 2, 3, 3, 0, 1]);
+    }
+
+    #[test]
+    fn build_adjacency_indices_welds_split_vertices() {
+        let mut positions = quad_positions();
+        positions.extend_from_slice(&[positions[2], positions[3], positions[0]]);
+        let adjacency = build_adjacency_indices(&positions, &[0, 1, 2, 4, 5, 6]);
+        assert_eq!(adjacency, [0, 0, 1, 1, 2, 5, 4, 4, 5, 5, 6, 1]);
+    }
+}
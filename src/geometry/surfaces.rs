@@ -7,6 +7,50 @@ use nalgebra_glm as glm;
 use super::{algorithms::de_casteljau, Vec3, Vec2};
 
 use super::primitives::{Mesh, Vertex};
+use super::Error;
+
+/// Control points of a bicubic Bézier patch
+pub type BicubicPatch = [[Vec3; 4]; 4];
+
+/// Parses bicubic Bézier patches stored as 16 control points per patch, one `x,y,z` point per line
+///
+/// Each patch can be fed to `BezierSurface::<4, 4>::new`.
+pub fn parse_bicubic_patches(source: &str) -> Result<Vec<BicubicPatch>, Error> {
+    let points = source
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            let coords = line
+                .split(',')
+                .map(|c| c.trim().parse::<f32>())
+                .collect::<Result<Vec<f32>, _>>()
+                .map_err(|_| Error::ParseError { line: index + 1 })?;
+            match coords[..] {
+                [x, y, z] => Ok(Vec3::new(x, y, z)),
+                _ => Err(Error::ParseError { line: index + 1 }),
+            }
+        })
+        .collect::<Result<Vec<Vec3>, Error>>()?;
+
+    if points.len() % 16 != 0 {
+        return Err(Error::IncompletePatch {
+            points: points.len(),
+        });
+    }
+
+    Ok(points
+        .chunks_exact(16)
+        .map(|chunk| {
+            let mut patch = [[Vec3::new(0.0, 0.0, 0.0); 4]; 4];
+            for (i, point) in chunk.iter().enumerate() {
+                patch[i / 4][i % 4] = *point;
+            }
+            patch
+        })
+        .collect())
+}
+
 pub struct BezierSurface<const M: usize, const N: usize> {
     ctrl_grid: [[Vec3; N]; M],
     mesh: Mesh,
@@ -54,6 +98,10 @@ impl<const M: usize, const N: usize> BezierSurface<M, N> {
             }
         }
         self.calculate_indices();
+        #[cfg(debug_assertions)]
+        if let Err(e) = self.mesh.validate_indices() {
+            log::error!("Invalid Bezier surface mesh: {:?}", e);
+        }
         // TODO : calculate normals with bezier derivative
         self.calculate_normals();
     }
@@ -115,3 +163,28 @@ impl<const M: usize, const N: usize> BezierSurface<M, N> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_bicubic_patches_reports_incomplete_patch() {
+        let result = parse_bicubic_patches("1,2,3\n\n\n4,5,6\n");
+        assert!(matches!(result, Err(Error::IncompletePatch { points: 2 })));
+    }
+
+    #[test]
+    fn parse_bicubic_patches_reports_bad_line() {
+        let result = parse_bicubic_patches("1,2,3\n\n1,2\n");
+        assert!(matches!(result, Err(Error::ParseError { line: 3 })));
+    }
+
+    #[test]
+    fn parse_bicubic_patches_groups_rows() {
+        let source: String = (0..16).map(|i| format!("{i},0,0\n")).collect();
+        let patches = parse_bicubic_patches(&source).unwrap();
+        assert_eq!(patches.len(), 1);
+        assert_eq!(patches[0][1][2], Vec3::new(6.0, 0.0, 0.0));
+    }
+}
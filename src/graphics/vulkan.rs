@@ -8,5 +8,10 @@ pub mod device;
 pub mod swapchain;
 pub mod graphics_pipeline;
 pub mod command_buffer;
+pub mod sampler;
+pub mod texture;
+pub mod vertex;
+pub mod std430;
+pub mod debug;
 mod errors;
 mod utils;
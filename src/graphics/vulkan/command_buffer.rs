@@ -2,11 +2,25 @@
 * SPDX-License-Identifier: MIT
 */
 
+use std::ffi::CString;
+use std::ops::Range;
 use std::rc::Rc;
 
 use ash::vk;
 
-use super::{device::VulkanDevice, errors::VulkanError, graphics_pipeline::VulkanGraphicsPipeline};
+use super::{
+    debug::Labeled, device::VulkanDevice, errors::VulkanError,
+    graphics_pipeline::VulkanGraphicsPipeline, texture::TextureFormat,
+};
+
+/// Corner of the render area where the viewport puts NDC `y = -1`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ViewportOrigin {
+    /// The Vulkan convention, matching image and texture coordinates
+    TopLeft,
+    /// The OpenGL convention, for projections and content made with a Y up screen space
+    BottomLeft,
+}
 
 pub struct VulkanCommandBuffer {
     device: Rc<VulkanDevice>,
@@ -33,6 +47,355 @@ impl VulkanCommandBuffer {
         // TODO
         Ok(())
     }
+
+    /// Sets the dynamic viewports starting at index 0
+    ///
+    /// Viewport selection is unusable for now: only a geometry stage can write
+    /// `SV_ViewportArrayIndex`, pipelines don't take one and the `geometry_shader` feature is not
+    /// enabled, so every primitive is drawn in viewport 0. Without the `multi_viewport` feature
+    /// pipelines are clamped to a single viewport and only the first one is set. An empty slice
+    /// records nothing.
+    pub fn set_viewports(&self, viewports: &[vk::Viewport]) {
+        if viewports.is_empty() {
+            return;
+        }
+        let viewports = if viewports.len() > 1 && self.device.features().multi_viewport != vk::TRUE
+        {
+            log::warn!(
+                "{} viewports given but pipelines are clamped to a single one, ignoring the others",
+                viewports.len()
+            );
+            &viewports[..1]
+        } else {
+            viewports
+        };
+        unsafe {
+            self.device
+                .logical_device()
+                .cmd_set_viewport(self.command_buffer, 0, viewports);
+        }
+    }
+
+    /// Sets a single dynamic viewport covering `area` which maps depth to `min_depth..max_depth`
+    ///
+    /// A narrow depth range keeps a layer in front of the rest of the scene, like a first person
+    /// weapon drawn in `0.0..0.1` over a world drawn in `0.1..1.0`. The range is clamped to
+    /// `0.0..=1.0` and may be reversed.
+    ///
+    /// A `BottomLeft` origin flips the viewport with a negative height, which also flips the
+    /// triangle winding seen by culling.
+    pub fn set_viewport(
+        &self,
+        area: vk::Rect2D,
+        origin: ViewportOrigin,
+        min_depth: f32,
+        max_depth: f32,
+    ) {
+        let (y, height) = match origin {
+            ViewportOrigin::TopLeft => (area.offset.y as f32, area.extent.height as f32),
+            ViewportOrigin::BottomLeft => (
+                (area.offset.y + area.extent.height as i32) as f32,
+                -(area.extent.height as f32),
+            ),
+        };
+        let viewport = vk::Viewport::default()
+            .x(area.offset.x as f32)
+            .y(y)
+            .width(area.extent.width as f32)
+            .height(height)
+            .min_depth(min_depth.clamp(0.0, 1.0))
+            .max_depth(max_depth.clamp(0.0, 1.0));
+        self.set_viewports(&[viewport]);
+    }
+
+    /// Records a non indexed draw
+    ///
+    /// Per instance vertex attributes are fetched starting at `first_instance`, which allows a
+    /// single instance buffer to serve several meshes
+    pub fn draw(
+        &self,
+        vertex_count: u32,
+        instance_count: u32,
+        first_vertex: u32,
+        first_instance: u32,
+    ) {
+        unsafe {
+            self.device.logical_device().cmd_draw(
+                self.command_buffer,
+                vertex_count,
+                instance_count,
+                first_vertex,
+                first_instance,
+            );
+        }
+    }
+
+    /// Records an indexed draw, `vertex_offset` is added to each index before fetching vertices
+    pub fn draw_indexed(
+        &self,
+        index_count: u32,
+        instance_count: u32,
+        first_index: u32,
+        vertex_offset: i32,
+        first_instance: u32,
+    ) {
+        unsafe {
+            self.device.logical_device().cmd_draw_indexed(
+                self.command_buffer,
+                index_count,
+                instance_count,
+                first_index,
+                vertex_offset,
+                first_instance,
+            );
+        }
+    }
+
+    /// Records an indexed draw of the contiguous `instances` sub-range of the instance buffer,
+    /// like one LOD or material group of a partitioned instance buffer
+    ///
+    /// Per instance attributes are fetched from `instances.start`, which is core Vulkan and
+    /// needs no fallback.
+    pub fn draw_indexed_instances(&self, index_count: u32, instances: Range<u32>) {
+        self.draw_indexed(
+            index_count,
+            instances.end.saturating_sub(instances.start),
+            0,
+            0,
+            instances.start,
+        );
+    }
+
+    /// Records `draw_count` indexed draws whose parameters are read from `buffer`
+    ///
+    /// `buffer` holds `vk::DrawIndexedIndirectCommand` entries `stride` bytes apart starting at
+    /// `offset`, it can be written by a compute shader. Without the `multi_draw_indirect` feature
+    /// one indirect draw is recorded per command. A non zero `first_instance` in a command
+    /// requires the `draw_indirect_first_instance` feature, check [`VulkanDevice::features`].
+    ///
+    /// `offset` must be a multiple of 4. When drawing more than one command `stride` must be a
+    /// multiple of 4 and at least the size of `vk::DrawIndexedIndirectCommand`, and with
+    /// `multi_draw_indirect` `draw_count` is limited to `max_draw_indirect_count`.
+    pub fn draw_indexed_indirect(
+        &self,
+        buffer: vk::Buffer,
+        offset: vk::DeviceSize,
+        draw_count: u32,
+        stride: u32,
+    ) -> Result<(), VulkanError> {
+        if !offset.is_multiple_of(4) {
+            return Err(VulkanError::InvalidLayout {
+                field: "indirect draw offset",
+                expected: offset.next_multiple_of(4) as usize,
+                actual: offset as usize,
+            });
+        }
+        let required = std::mem::size_of::<vk::DrawIndexedIndirectCommand>() as u32;
+        if draw_count > 1 && (!stride.is_multiple_of(4) || stride < required) {
+            return Err(VulkanError::InvalidIndirectStride { stride, required });
+        }
+        let max = self.device.limits().max_draw_indirect_count;
+        if draw_count > max && self.device.features().multi_draw_indirect == vk::TRUE {
+            return Err(VulkanError::LimitExceeded {
+                limit: "max draw indirect count",
+                requested: draw_count,
+                max,
+            });
+        }
+        let device = self.device.logical_device();
+        unsafe {
+            if draw_count <= 1 || self.device.features().multi_draw_indirect == vk::TRUE {
+                device.cmd_draw_indexed_indirect(
+                    self.command_buffer,
+                    buffer,
+                    offset,
+                    draw_count,
+                    stride,
+                );
+            } else {
+                for draw in 0..draw_count as vk::DeviceSize {
+                    device.cmd_draw_indexed_indirect(
+                        self.command_buffer,
+                        buffer,
+                        offset + draw * stride as vk::DeviceSize,
+                        1,
+                        stride,
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Records a fullscreen triangle draw without any vertex buffer
+    ///
+    /// The bound pipeline must be created without vertex attributes and use the vertex shader
+    /// of `shaders/fullscreen.hlsl`, compiled to `assets/spirv/fullscreen.vert.spv`
+    pub fn draw_fullscreen(&self) {
+        self.draw(3, 1, 0, 0);
+    }
+
+    /// Records a scaled copy of `src_rect` of `src` into `dst_rect` of `dst`
+    ///
+    /// Rects are given as `[min, max)` corners, swapping them mirrors the copy. `src` must be in
+    /// `TRANSFER_SRC_OPTIMAL` layout and `dst` in `TRANSFER_DST_OPTIMAL`. `aspect` selects color,
+    /// depth or stencil, the filter is forced to `NEAREST` for depth and stencil.
+    pub fn blit_image(
+        &self,
+        src: vk::Image,
+        src_rect: [vk::Offset3D; 2],
+        dst: vk::Image,
+        dst_rect: [vk::Offset3D; 2],
+        filter: vk::Filter,
+        aspect: vk::ImageAspectFlags,
+    ) {
+        let subresource = vk::ImageSubresourceLayers::default()
+            .aspect_mask(aspect)
+            .mip_level(0)
+            .base_array_layer(0)
+            .layer_count(1);
+        let region = vk::ImageBlit::default()
+            .src_subresource(subresource)
+            .src_offsets(src_rect)
+            .dst_subresource(subresource)
+            .dst_offsets(dst_rect);
+        let filter = if aspect.contains(vk::ImageAspectFlags::COLOR) {
+            filter
+        } else {
+            vk::Filter::NEAREST
+        };
+        unsafe {
+            self.device.logical_device().cmd_blit_image(
+                self.command_buffer,
+                src,
+                vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                dst,
+                vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                &[region],
+                filter,
+            );
+        }
+    }
+
+    /// Opens a named group of commands shown as a tree node in GPU debuggers, groups nest and
+    /// must be closed with [`Self::pop_debug_group`] in the same command buffer
+    ///
+    /// Does nothing without `VK_EXT_debug_utils`.
+    pub fn push_debug_group(&self, name: &str) -> Result<(), VulkanError> {
+        if let Some(debug_utils) = self.device.debug_utils() {
+            let name = CString::new(name)?;
+            let label = vk::DebugUtilsLabelEXT::default().label_name(&name);
+            unsafe { debug_utils.cmd_begin_debug_utils_label(self.command_buffer, &label) };
+        }
+        Ok(())
+    }
+
+    /// Closes the innermost debug group
+    pub fn pop_debug_group(&self) {
+        if let Some(debug_utils) = self.device.debug_utils() {
+            unsafe { debug_utils.cmd_end_debug_utils_label(self.command_buffer) };
+        }
+    }
+
+    /// Opens a debug group closed when the returned guard is dropped
+    pub fn debug_group(&self, name: &str) -> Result<DebugGroup<'_>, VulkanError> {
+        self.push_debug_group(name)?;
+        Ok(DebugGroup {
+            command_buffer: self,
+        })
+    }
+
+    /// Records a texel exact copy of `src_rect` of `src` to `dst_origin` in `dst`, without
+    /// filtering or format conversion and without a render pass
+    ///
+    /// The layouts are the same as for [`Self::blit_image`], used for texture streaming and
+    /// atlas updates.
+    pub fn copy_image(
+        &self,
+        src: vk::Image,
+        src_format: TextureFormat,
+        src_rect: vk::Rect2D,
+        dst: vk::Image,
+        dst_format: TextureFormat,
+        dst_origin: vk::Offset2D,
+    ) -> Result<(), VulkanError> {
+        if !src_format.is_copy_compatible(dst_format) {
+            return Err(VulkanError::IncompatibleFormats {
+                src: src_format.vk_format(),
+                dst: dst_format.vk_format(),
+            });
+        }
+        let subresource = |format: TextureFormat| {
+            vk::ImageSubresourceLayers::default()
+                .aspect_mask(format.aspect_mask())
+                .mip_level(0)
+                .base_array_layer(0)
+                .layer_count(1)
+        };
+        let src_offset = vk::Offset3D::default()
+            .x(src_rect.offset.x)
+            .y(src_rect.offset.y);
+        let dst_offset = vk::Offset3D::default().x(dst_origin.x).y(dst_origin.y);
+        let region = vk::ImageCopy::default()
+            .src_subresource(subresource(src_format))
+            .src_offset(src_offset)
+            .dst_subresource(subresource(dst_format))
+            .dst_offset(dst_offset)
+            .extent(src_rect.extent.into());
+        unsafe {
+            self.device.logical_device().cmd_copy_image(
+                self.command_buffer,
+                src,
+                vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                dst,
+                vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                &[region],
+            );
+        }
+        Ok(())
+    }
+
+    /// Sets the dynamic scissors starting at index 0, one per viewport
+    ///
+    /// Like viewports only the first scissor is set without the `multi_viewport` feature, an
+    /// empty slice records nothing.
+    pub fn set_scissors(&self, scissors: &[vk::Rect2D]) {
+        if scissors.is_empty() {
+            return;
+        }
+        let scissors = if scissors.len() > 1 && self.device.features().multi_viewport != vk::TRUE {
+            log::warn!(
+                "{} scissors given but pipelines are clamped to a single one, ignoring the others",
+                scissors.len()
+            );
+            &scissors[..1]
+        } else {
+            scissors
+        };
+        unsafe {
+            self.device
+                .logical_device()
+                .cmd_set_scissor(self.command_buffer, 0, scissors);
+        }
+    }
+}
+
+/// Open debug group of a command buffer, see [`VulkanCommandBuffer::debug_group`]
+pub struct DebugGroup<'a> {
+    command_buffer: &'a VulkanCommandBuffer,
+}
+
+impl Drop for DebugGroup<'_> {
+    fn drop(&mut self) {
+        self.command_buffer.pop_debug_group();
+    }
+}
+
+impl Labeled for VulkanCommandBuffer {
+    fn set_label(&self, label: &str) -> Result<(), VulkanError> {
+        self.device.set_object_name(self.command_buffer, label)?;
+        self.device.set_object_name(self.command_pool, label)
+    }
 }
 
 impl Drop for VulkanCommandBuffer {
@@ -178,8 +178,11 @@ impl VulkanContext {
             #[cfg(debug_assertions)]
             let (dbg_instance, dbg_messenger) = utils::create_debug_messenger(&entry, &instance)?;
 
-            let device_extensions = vec![ash::khr::swapchain::NAME.to_owned()];
-            //device_extensions.push();
+            // maintenance1 allows negative viewport heights, it is core since Vulkan 1.1
+            let device_extensions = vec![
+                ash::khr::swapchain::NAME.to_owned(),
+                ash::khr::maintenance1::NAME.to_owned(),
+            ];
             let devices = instance
                 .enumerate_physical_devices()?
                 .iter()
@@ -228,8 +231,13 @@ impl VulkanContext {
         }
     }
 
+    /// Creates a device on the most capable suitable GPU, discrete ones first
+    ///
+    /// `pipeline_cache_data` seeds the pipeline cache with the content saved from a previous
+    /// [`device::VulkanDevice::pipeline_cache_data`]
     pub fn create_graphic_device_default(
         self: &Rc<Self>,
+        pipeline_cache_data: Option<&[u8]>,
     ) -> Result<device::VulkanDevice, errors::VulkanError> {
         let mut selected_device = None;
         let mut selected_device_type = vk::PhysicalDeviceType::OTHER;
@@ -256,7 +264,7 @@ impl VulkanContext {
             index += 1;
         }
         match selected_device {
-            Some(idx) => self.create_graphic_device(idx),
+            Some(idx) => self.create_graphic_device(idx, pipeline_cache_data),
             None => Err(errors::VulkanError::DeviceSelectionError),
         }
     }
@@ -264,6 +272,7 @@ impl VulkanContext {
     pub fn create_graphic_device(
         self: &Rc<Self>,
         index: usize,
+        pipeline_cache_data: Option<&[u8]>,
     ) -> Result<device::VulkanDevice, errors::VulkanError> {
         let graphics_device = self.physical_devices.get(index);
 
@@ -276,6 +285,7 @@ impl VulkanContext {
                         graphics_family_index,
                         present_family_index,
                         &self.device_extensions,
+                        pipeline_cache_data,
                     )
                 }
                 _ => Err(errors::VulkanError::DeviceSelectionError),
@@ -284,6 +294,11 @@ impl VulkanContext {
         }
     }
 
+    /// Returns whether `VK_EXT_debug_utils` is enabled, which is the case in debug builds
+    pub(crate) fn has_debug_utils(&self) -> bool {
+        self.dbg_instance.is_some()
+    }
+
     pub fn entry(&self) -> &ash::Entry {
         &self.entry
     }
@@ -0,0 +1,13 @@
+/*
+* SPDX-License-Identifier: MIT
+*/
+
+use super::errors::VulkanError;
+
+/// Objects that can be named in GPU debuggers like RenderDoc or Nsight
+///
+/// Names go through `VK_EXT_debug_utils`, which is only enabled in debug builds,
+/// elsewhere setting a label does nothing.
+pub trait Labeled {
+    fn set_label(&self, label: &str) -> Result<(), VulkanError>;
+}
@@ -11,10 +11,13 @@ use super::command_buffer::VulkanCommandBuffer;
 use super::context::VulkanContext;
 use super::errors;
 use super::errors::VulkanError;
-use super::graphics_pipeline::VulkanGraphicsPipeline;
+use super::graphics_pipeline::{PipelineState, VulkanGraphicsPipeline};
+use super::sampler::{SamplerState, VulkanSampler};
 use super::swapchain;
 use super::swapchain::VulkanSwapChain;
+use super::texture::TextureFormat;
 use super::utils;
+use super::vertex::{self, VertexDesc};
 
 pub struct VulkanDevice {
     context: Rc<VulkanContext>,
@@ -24,6 +27,10 @@ pub struct VulkanDevice {
     present_queue: vk::Queue,
     graphics_family_index: u32,
     present_family_index: u32,
+    features: vk::PhysicalDeviceFeatures,
+    limits: vk::PhysicalDeviceLimits,
+    debug_utils: Option<ash::ext::debug_utils::Device>,
+    pipeline_cache: vk::PipelineCache,
 }
 
 impl VulkanDevice {
@@ -33,13 +40,18 @@ impl VulkanDevice {
         graphics_family_index: u32,
         present_family_index: u32,
         extensions: &[ffi::CString],
+        pipeline_cache_data: Option<&[u8]>,
     ) -> Result<Self, errors::VulkanError> {
         let device_properties = unsafe {
             context
                 .instance()
                 .get_physical_device_properties(physical_device)
         };
-        //let device_features = unsafe { instance.get_physical_device_features(*physical_device) };
+        let supported_features = unsafe {
+            context
+                .instance()
+                .get_physical_device_features(physical_device)
+        };
 
         let device_type = match device_properties.device_type {
             vk::PhysicalDeviceType::CPU => "Cpu",
@@ -83,12 +95,40 @@ impl VulkanDevice {
         dev_create_info = dev_create_info.enabled_extension_names(&extensions_raw);
         dev_create_info.enabled_extension_count = extensions.len() as u32;
 
+        // only enable the optional features the device supports, pipelines check them before use
+        let features = vk::PhysicalDeviceFeatures::default()
+            .sample_rate_shading(supported_features.sample_rate_shading == vk::TRUE)
+            .multi_viewport(supported_features.multi_viewport == vk::TRUE)
+            .depth_clamp(supported_features.depth_clamp == vk::TRUE)
+            .logic_op(supported_features.logic_op == vk::TRUE)
+            .sampler_anisotropy(supported_features.sampler_anisotropy == vk::TRUE)
+            .multi_draw_indirect(supported_features.multi_draw_indirect == vk::TRUE)
+            .draw_indirect_first_instance(
+                supported_features.draw_indirect_first_instance == vk::TRUE,
+            )
+            .independent_blend(supported_features.independent_blend == vk::TRUE)
+            .shader_clip_distance(supported_features.shader_clip_distance == vk::TRUE)
+            .fill_mode_non_solid(supported_features.fill_mode_non_solid == vk::TRUE)
+            .wide_lines(supported_features.wide_lines == vk::TRUE);
+        dev_create_info = dev_create_info.enabled_features(&features);
+
         let logical_device = unsafe {
             context
                 .instance()
                 .create_device(physical_device, &dev_create_info, None)?
         };
 
+        let debug_utils = context
+            .has_debug_utils()
+            .then(|| ash::ext::debug_utils::Device::new(context.instance(), &logical_device));
+
+        // shared by every pipeline so that identical shader stages and states compile once,
+        // drivers check the header of the initial data and ignore it when it doesn't match
+        let pipeline_cache_info =
+            vk::PipelineCacheCreateInfo::default().initial_data(pipeline_cache_data.unwrap_or(&[]));
+        let pipeline_cache =
+            unsafe { logical_device.create_pipeline_cache(&pipeline_cache_info, None)? };
+
         let graphics_queue = unsafe { logical_device.get_device_queue(graphics_family_index, 0) };
         let present_queue = unsafe { logical_device.get_device_queue(present_family_index, 0) };
 
@@ -100,6 +140,10 @@ impl VulkanDevice {
             present_queue,
             graphics_family_index,
             present_family_index,
+            features,
+            limits: device_properties.limits,
+            debug_utils,
+            pipeline_cache,
         })
     }
 
@@ -137,10 +181,118 @@ impl VulkanDevice {
         &self.device
     }
 
+    /// Returns the optional features enabled on this device
+    pub fn features(&self) -> &vk::PhysicalDeviceFeatures {
+        &self.features
+    }
+
+    /// Returns the limits of the physical device
+    pub fn limits(&self) -> &vk::PhysicalDeviceLimits {
+        &self.limits
+    }
+
+    /// Returns the debug utils functions when `VK_EXT_debug_utils` is enabled
+    pub(crate) fn debug_utils(&self) -> Option<&ash::ext::debug_utils::Device> {
+        self.debug_utils.as_ref()
+    }
+
+    /// Returns the content of the pipeline cache, which can be saved to speed up pipeline
+    /// creation on the next run of the same driver by passing it back at device creation
+    pub fn pipeline_cache_data(&self) -> Result<Vec<u8>, VulkanError> {
+        Ok(unsafe { self.device.get_pipeline_cache_data(self.pipeline_cache)? })
+    }
+
+    /// Names `handle` in GPU debuggers and validation messages, does nothing without
+    /// `VK_EXT_debug_utils`
+    pub fn set_object_name<T: vk::Handle>(&self, handle: T, name: &str) -> Result<(), VulkanError> {
+        if let Some(debug_utils) = &self.debug_utils {
+            let name = ffi::CString::new(name)?;
+            let name_info = vk::DebugUtilsObjectNameInfoEXT::default()
+                .object_handle(handle)
+                .object_name(&name);
+            unsafe { debug_utils.set_debug_utils_object_name(&name_info)? };
+        }
+        Ok(())
+    }
+
+    /// Returns whether optimally tiled images of `format` support all of `features`,
+    /// like `COLOR_ATTACHMENT_BLEND` for an HDR render target or `SAMPLED_IMAGE_FILTER_LINEAR`
+    pub fn supports_format(&self, format: TextureFormat, features: vk::FormatFeatureFlags) -> bool {
+        let properties = unsafe {
+            self.context
+                .instance()
+                .get_physical_device_format_properties(self.phy, format.vk_format())
+        };
+        properties.optimal_tiling_features.contains(features)
+    }
+
+    /// Returns the first of `candidates`, in order of preference, supporting `features`
+    pub fn pick_format(
+        &self,
+        candidates: &[TextureFormat],
+        features: vk::FormatFeatureFlags,
+    ) -> Option<TextureFormat> {
+        candidates
+            .iter()
+            .copied()
+            .find(|&format| self.supports_format(format, features))
+    }
+
+    /// Returns how many user clip distances shaders may write, 0 without the
+    /// `shader_clip_distance` feature
+    ///
+    /// A vertex shader output `float d : SV_ClipDistanceN` clips the primitive where `d < 0`,
+    /// like the water plane of a reflection pass. There is nothing to enable per distance, a
+    /// pipeline clips on every distance its shader writes.
+    pub fn max_clip_distances(&self) -> u32 {
+        if self.features.shader_clip_distance == vk::TRUE {
+            self.limits.max_clip_distances
+        } else {
+            0
+        }
+    }
+
+    /// Rounds `offset` up to the alignment required for dynamic uniform buffer offsets
+    pub fn aligned_uniform_offset(&self, offset: usize) -> usize {
+        utils::align_up(
+            offset,
+            self.limits.min_uniform_buffer_offset_alignment as usize,
+        )
+    }
+
+    /// Rounds `offset` up to the alignment required for storage buffer offsets
+    pub fn aligned_storage_offset(&self, offset: usize) -> usize {
+        utils::align_up(
+            offset,
+            self.limits.min_storage_buffer_offset_alignment as usize,
+        )
+    }
+
+    /// Blocks until all work submitted to the graphics queue has completed
+    pub fn wait_graphics_queue_idle(&self) -> Result<(), VulkanError> {
+        unsafe { self.device.queue_wait_idle(self.graphics_queue)? };
+        Ok(())
+    }
+
+    /// Blocks until the whole device is idle
+    ///
+    /// This stalls the CPU until the GPU has finished every submitted command,
+    /// it should only be used for benchmarking, screenshots or before destroying resources.
+    /// A `VulkanError::DeviceLost` means the GPU was reset and everything must be recreated.
+    pub fn wait_idle(&self) -> Result<(), VulkanError> {
+        unsafe { self.device.device_wait_idle()? };
+        Ok(())
+    }
+
+    /// Creates a swapchain for the context surface
+    ///
+    /// With `srgb` the images use an sRGB format and the linear shader output is gamma encoded
+    /// on write, without it the output is presented unchanged.
     pub fn create_swapchain(
         self: &Rc<Self>,
         width: u32,
         height: u32,
+        srgb: bool,
     ) -> swapchain::VulkanSwapChain {
         let (image_sharing_mode, queue_family_indices) =
             if self.graphics_family_index != self.present_family_index {
@@ -156,6 +308,7 @@ impl VulkanDevice {
             self.clone(),
             width,
             height,
+            srgb,
             image_sharing_mode,
             &queue_family_indices,
         )
@@ -201,20 +354,113 @@ impl VulkanDevice {
         unsafe { self.device.create_render_pass(&render_pass_info, None) }
     }
 
+    /// Turns values the driver would silently mishandle into descriptive errors,
+    /// `strides` are the resolved strides of the vertex and instance bindings
+    fn check_pipeline_limits(
+        &self,
+        state: &PipelineState,
+        strides: &[u32],
+    ) -> Result<(), VulkanError> {
+        let attributes = &state.vertex_input.attributes;
+        let check = |limit, requested, max| {
+            if requested > max {
+                Err(VulkanError::LimitExceeded {
+                    limit,
+                    requested,
+                    max,
+                })
+            } else {
+                Ok(())
+            }
+        };
+        let limits = &self.limits;
+        check(
+            "max vertex attributes",
+            attributes.len() as u32,
+            limits.max_vertex_input_attributes,
+        )?;
+        for attribute in attributes {
+            check(
+                "max vertex attribute location",
+                attribute.location,
+                limits.max_vertex_input_attributes - 1,
+            )?;
+            check(
+                "max vertex attribute offset",
+                attribute.offset,
+                limits.max_vertex_input_attribute_offset,
+            )?;
+        }
+        for &stride in strides {
+            check(
+                "max vertex stride",
+                stride,
+                limits.max_vertex_input_binding_stride,
+            )?;
+        }
+        if state.viewport_count == 0 {
+            return Err(VulkanError::NoViewport);
+        }
+        // unsupported features are ignored with a warning at pipeline creation
+        if self.features.multi_viewport == vk::TRUE {
+            check("max viewports", state.viewport_count, limits.max_viewports)?;
+        }
+        Ok(())
+    }
+
     pub fn create_graphics_pipeline(
         self: &Rc<Self>,
         swapchain: VulkanSwapChain,
-        stride: u32,
+        state: &PipelineState,
         vertex_shader: &[u8],
         vs_entrypoint: &ffi::CStr,
         fragment_shader: &[u8],
         fs_entrypoint: &ffi::CStr,
         specializations: Option<&Vec<(u32, u32)>>,
     ) -> Result<VulkanGraphicsPipeline, VulkanError> {
-        let render_pass = unsafe { self.create_render_pass(swapchain.format)? };
+        // a patch list pipeline without tessellation stages is invalid
+        if state.topology == vk::PrimitiveTopology::PATCH_LIST {
+            return Err(VulkanError::UnsupportedTopology(state.topology));
+        }
+        // falling back to FILL would cover the mesh with the wireframe overlay triangles
+        if state.polygon_mode != vk::PolygonMode::FILL
+            && self.features.fill_mode_non_solid != vk::TRUE
+        {
+            return Err(VulkanError::UnsupportedFeature("fill_mode_non_solid"));
+        }
+
+        let stride = state.vertex_input.stride;
+        let instance_stride = state.vertex_input.instance_stride;
+        let attributes = &state.vertex_input.attributes[..];
+        let (per_instance, per_vertex): (Vec<VertexDesc>, Vec<VertexDesc>) = attributes
+            .iter()
+            .partition(|attribute| attribute.per_instance);
+        vertex::validate_attributes(&per_vertex, stride)?;
+        vertex::validate_attributes(&per_instance, instance_stride)?;
+        // a stride of 0 means the attributes are tightly packed
+        let stride = if stride == 0 {
+            vertex::packed_stride(&per_vertex)
+        } else {
+            stride
+        };
+        let instance_stride = if instance_stride == 0 {
+            vertex::packed_stride(&per_instance)
+        } else {
+            instance_stride
+        };
+        self.check_pipeline_limits(state, &[stride, instance_stride])?;
 
+        // every handle created below is destroyed again when a later creation fails
         let vertex_shader_mod = unsafe { self.create_shader_module(vertex_shader)? };
-        let fragment_shader_mod = unsafe { self.create_shader_module(fragment_shader)? };
+        let fragment_shader_mod = unsafe { self.create_shader_module(fragment_shader) }
+            .inspect_err(|_| unsafe {
+                self.device.destroy_shader_module(vertex_shader_mod, None)
+            })?;
+        // the modules are no longer needed once the pipeline is built, or failed to build
+        let destroy_shader_modules = || unsafe {
+            self.device.destroy_shader_module(vertex_shader_mod, None);
+            self.device.destroy_shader_module(fragment_shader_mod, None);
+        };
 
         let entries;
         let buffer;
@@ -246,84 +492,238 @@ impl VulkanDevice {
         let dynamic_states = [vk::DynamicState::VIEWPORT, vk::DynamicState::SCISSOR];
         let dynamic_states_info =
             vk::PipelineDynamicStateCreateInfo::default().dynamic_states(&dynamic_states);
-        let vertex_bindings = [vk::VertexInputBindingDescription::default()
-            .binding(0)
-            .stride(stride)
-            .input_rate(vk::VertexInputRate::VERTEX)];
+
+        // per vertex attributes come from binding 0 and per instance ones from binding 1
+        let mut vertex_bindings = Vec::new();
+        if !per_vertex.is_empty() {
+            vertex_bindings.push(
+                vk::VertexInputBindingDescription::default()
+                    .binding(0)
+                    .stride(stride)
+                    .input_rate(vk::VertexInputRate::VERTEX),
+            );
+        }
+        if !per_instance.is_empty() {
+            vertex_bindings.push(
+                vk::VertexInputBindingDescription::default()
+                    .binding(1)
+                    .stride(instance_stride)
+                    .input_rate(vk::VertexInputRate::INSTANCE),
+            );
+        }
+
+        let vertex_attributes = attributes
+            .iter()
+            .map(|attribute| {
+                vk::VertexInputAttributeDescription::default()
+                    .binding(u32::from(attribute.per_instance))
+                    .location(attribute.location)
+                    .format(attribute.format)
+                    .offset(attribute.offset)
+            })
+            .collect::<Vec<vk::VertexInputAttributeDescription>>();
 
         let vertex_input_info = vk::PipelineVertexInputStateCreateInfo::default()
-            .vertex_binding_descriptions(&vertex_bindings);
-        // TODO return here to add missing vertex attribute description
+            .vertex_binding_descriptions(&vertex_bindings)
+            .vertex_attribute_descriptions(&vertex_attributes);
+
+        // restart is only allowed on strips and fans without the primitiveTopologyListRestart
+        // features of VK_EXT_primitive_topology_list_restart
+        let is_list = matches!(
+            state.topology,
+            vk::PrimitiveTopology::POINT_LIST
+                | vk::PrimitiveTopology::LINE_LIST
+                | vk::PrimitiveTopology::TRIANGLE_LIST
+                | vk::PrimitiveTopology::LINE_LIST_WITH_ADJACENCY
+                | vk::PrimitiveTopology::TRIANGLE_LIST_WITH_ADJACENCY
+                | vk::PrimitiveTopology::PATCH_LIST
+        );
+        let primitive_restart = if state.primitive_restart && is_list {
+            log::warn!(
+                "Primitive restart is not supported with {:?}, ignoring it",
+                state.topology
+            );
+            false
+        } else {
+            state.primitive_restart
+        };
 
-        // TODO : make this also configurable
         let input_asm = vk::PipelineInputAssemblyStateCreateInfo::default()
-            .topology(vk::PrimitiveTopology::TRIANGLE_LIST)
-            .primitive_restart_enable(false); // TODO allows to break strip with 0xFFFF
+            .topology(state.topology)
+            .primitive_restart_enable(primitive_restart);
+
+        let viewport_count = if state.viewport_count > 1 && self.features.multi_viewport != vk::TRUE
+        {
+            log::warn!("Multiple viewports are not supported by the device, using a single one");
+            1
+        } else {
+            state.viewport_count
+        };
 
         let viewport_state = vk::PipelineViewportStateCreateInfo::default()
-            .viewport_count(1)
-            .scissor_count(1);
+            .viewport_count(viewport_count)
+            .scissor_count(viewport_count);
+
+        let depth_clamp = if state.depth_clamp && self.features.depth_clamp != vk::TRUE {
+            log::warn!("Depth clamp is not supported by the device, ignoring it");
+            false
+        } else {
+            state.depth_clamp
+        };
+
+        let line_width = if state.line_width != 1.0 && self.features.wide_lines != vk::TRUE {
+            log::warn!("Wide lines are not supported by the device, ignoring it");
+            1.0
+        } else {
+            let [min_width, max_width] = self.limits.line_width_range;
+            state.line_width.clamp(min_width, max_width)
+        };
 
-        let rasterizer_state = vk::PipelineRasterizationStateCreateInfo::default()
-            .depth_clamp_enable(false)
+        let mut rasterizer_state = vk::PipelineRasterizationStateCreateInfo::default()
+            .depth_clamp_enable(depth_clamp)
             .rasterizer_discard_enable(false)
-            .polygon_mode(vk::PolygonMode::FILL)
-            .cull_mode(vk::CullModeFlags::BACK)
-            .front_face(vk::FrontFace::CLOCKWISE)
-            .depth_bias_enable(false); // necessary for shadow mapping
-
-        let multisample_state = vk::PipelineMultisampleStateCreateInfo::default()
-            .sample_shading_enable(false) //TODO: multisampling is disabled for now
-            .rasterization_samples(vk::SampleCountFlags::TYPE_1);
-        // TODO: depth and stencil buffer
-
-        // color blending
-        let color_blend_attachments = [vk::PipelineColorBlendAttachmentState::default()
-            .blend_enable(false)
-            .color_write_mask(vk::ColorComponentFlags::RGBA)
-            .src_color_blend_factor(vk::BlendFactor::ONE)
-            .dst_color_blend_factor(vk::BlendFactor::ZERO)
-            .color_blend_op(vk::BlendOp::ADD)
-            .src_alpha_blend_factor(vk::BlendFactor::ONE)
-            .dst_alpha_blend_factor(vk::BlendFactor::ZERO)
-            .alpha_blend_op(vk::BlendOp::ADD)];
+            .polygon_mode(state.polygon_mode)
+            .line_width(line_width)
+            .cull_mode(state.cull_mode)
+            .front_face(state.front_face)
+            .depth_bias_enable(false);
+
+        if let Some((constant_factor, slope_factor)) = state.depth_bias {
+            rasterizer_state = rasterizer_state
+                .depth_bias_enable(true)
+                .depth_bias_constant_factor(constant_factor)
+                .depth_bias_slope_factor(slope_factor);
+        }
+
+        let sample_mask = state.sample_mask.map(|mask| [mask]);
+
+        let mut multisample_state = vk::PipelineMultisampleStateCreateInfo::default()
+            .sample_shading_enable(false)
+            .rasterization_samples(vk::SampleCountFlags::TYPE_1); //TODO: multisampling is disabled for now
+
+        if let Some(min_sample_shading) = state.sample_shading {
+            if self.features.sample_rate_shading == vk::TRUE {
+                multisample_state = multisample_state
+                    .sample_shading_enable(true)
+                    .min_sample_shading(min_sample_shading.clamp(0.0, 1.0));
+            } else {
+                log::warn!("Sample rate shading is not supported by the device, ignoring it");
+            }
+        }
+
+        if let Some(sample_mask) = sample_mask.as_ref() {
+            multisample_state = multisample_state.sample_mask(sample_mask);
+        }
+
+        // TODO: depth and stencil buffer attachments
+        let depth_stencil_state = vk::PipelineDepthStencilStateCreateInfo::default()
+            .depth_test_enable(state.depth_test.is_some())
+            .depth_compare_op(state.depth_test.unwrap_or(vk::CompareOp::ALWAYS))
+            .depth_write_enable(state.depth_write);
+
+        // color blending, one state per color attachment of the render pass
+        // TODO: render passes have a single color attachment for now
+        let color_attachment_count = 1;
+        if state.blend.len() > color_attachment_count {
+            log::warn!(
+                "{} blend states given for {} color attachments, ignoring the extra ones",
+                state.blend.len(),
+                color_attachment_count
+            );
+        }
+        let mut blend = state.blend.clone();
+        blend.resize(color_attachment_count, None);
+        if blend.windows(2).any(|pair| pair[0] != pair[1])
+            && self.features.independent_blend != vk::TRUE
+        {
+            log::warn!("Independent blend is not supported by the device, using the first state");
+            let first = blend[0];
+            blend.fill(first);
+        }
+
+        let color_blend_attachments = blend
+            .iter()
+            .map(|blend| {
+                let attachment = vk::PipelineColorBlendAttachmentState::default()
+                    .color_write_mask(state.color_write_mask);
+                match blend {
+                    Some(blend) => attachment
+                        .blend_enable(true)
+                        .src_color_blend_factor(blend.src_color)
+                        .dst_color_blend_factor(blend.dst_color)
+                        .color_blend_op(blend.color_op)
+                        .src_alpha_blend_factor(blend.src_alpha)
+                        .dst_alpha_blend_factor(blend.dst_alpha)
+                        .alpha_blend_op(blend.alpha_op),
+                    None => attachment
+                        .blend_enable(false)
+                        .src_color_blend_factor(vk::BlendFactor::ONE)
+                        .dst_color_blend_factor(vk::BlendFactor::ZERO)
+                        .color_blend_op(vk::BlendOp::ADD)
+                        .src_alpha_blend_factor(vk::BlendFactor::ONE)
+                        .dst_alpha_blend_factor(vk::BlendFactor::ZERO)
+                        .alpha_blend_op(vk::BlendOp::ADD),
+                }
+            })
+            .collect::<Vec<vk::PipelineColorBlendAttachmentState>>();
+
+        let logic_op = match state.logic_op {
+            Some(_) if self.features.logic_op != vk::TRUE => {
+                log::warn!("Logic operations are not supported by the device, ignoring it");
+                None
+            }
+            op => op,
+        };
 
         let color_blend_state = vk::PipelineColorBlendStateCreateInfo::default()
-            .logic_op_enable(false)
-            .logic_op(vk::LogicOp::COPY)
+            .logic_op_enable(logic_op.is_some())
+            .logic_op(logic_op.unwrap_or(vk::LogicOp::COPY))
             .attachments(&color_blend_attachments)
             .blend_constants([0.0, 0.0, 0.0, 0.0]);
 
         let pipeline_layout_info = vk::PipelineLayoutCreateInfo::default();
 
+        let render_pass = unsafe { self.create_render_pass(swapchain.format) }
+            .inspect_err(|_| destroy_shader_modules())?;
+
         let pipeline_layout = unsafe {
             self.device
-                .create_pipeline_layout(&pipeline_layout_info, None)?
-        };
+                .create_pipeline_layout(&pipeline_layout_info, None)
+        }
+        .inspect_err(|_| {
+            destroy_shader_modules();
+            unsafe { self.device.destroy_render_pass(render_pass, None) };
+        })?;
 
-        let pipeline_infos = [vk::GraphicsPipelineCreateInfo::default()
+        let pipeline_info = vk::GraphicsPipelineCreateInfo::default()
             .stages(&shader_stages)
             .vertex_input_state(&vertex_input_info)
             .input_assembly_state(&input_asm)
             .viewport_state(&viewport_state)
             .rasterization_state(&rasterizer_state)
             .multisample_state(&multisample_state)
+            .depth_stencil_state(&depth_stencil_state)
             .color_blend_state(&color_blend_state)
             .dynamic_state(&dynamic_states_info)
             .layout(pipeline_layout)
             .render_pass(render_pass)
-            .subpass(0)];
+            .subpass(0);
+
+        let pipeline_infos = [pipeline_info];
 
         let pipeline = unsafe {
             self.device
-                .create_graphics_pipelines(vk::PipelineCache::null(), &pipeline_infos, None)
-                .expect("failed to create graphics pipeline")
+                .create_graphics_pipelines(self.pipeline_cache, &pipeline_infos, None)
         };
 
-        unsafe {
-            self.device.destroy_shader_module(vertex_shader_mod, None);
-            self.device.destroy_shader_module(fragment_shader_mod, None);
-        }
+        destroy_shader_modules();
+        let pipeline = pipeline.map_err(|(_, e)| {
+            unsafe {
+                self.device.destroy_pipeline_layout(pipeline_layout, None);
+                self.device.destroy_render_pass(render_pass, None);
+            }
+            VulkanError::from(e)
+        })?;
 
         Ok(VulkanGraphicsPipeline::new(
             self.clone(),
@@ -333,6 +733,17 @@ impl VulkanDevice {
         ))
     }
 
+    pub fn create_sampler(
+        self: &Rc<Self>,
+        state: &SamplerState,
+    ) -> Result<VulkanSampler, VulkanError> {
+        let create_info = state.create_info(self);
+
+        let sampler = unsafe { self.device.create_sampler(&create_info, None)? };
+
+        Ok(VulkanSampler::new(self.clone(), sampler))
+    }
+
     pub fn create_command_buffer(self: &Rc<Self>) -> Result<VulkanCommandBuffer, VulkanError> {
         let pool_info = vk::CommandPoolCreateInfo::default()
             .queue_family_index(self.graphics_family_index)
@@ -358,6 +769,10 @@ impl VulkanDevice {
 
 impl Drop for VulkanDevice {
     fn drop(&mut self) {
-        unsafe { self.device.destroy_device(None) };
+        unsafe {
+            self.device
+                .destroy_pipeline_cache(self.pipeline_cache, None);
+            self.device.destroy_device(None);
+        };
     }
 }
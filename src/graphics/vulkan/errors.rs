@@ -11,6 +11,38 @@ pub enum VulkanError {
     VulkanError(vk::Result),
     DeviceSelectionError,
     StringError,
+    InvalidTextureData { expected: usize, actual: usize },
+    /// Host or device memory is exhausted, the failed allocation left nothing to clean up
+    /// but the caller should free resources or lower quality before retrying
+    OutOfMemory,
+    /// The device was reset after a hang or a driver crash, the device and every resource
+    /// created from it must be dropped and recreated
+    DeviceLost,
+    /// The pipeline requires shader stages that can't be created yet
+    UnsupportedTopology(vk::PrimitiveTopology),
+    /// The state requires a device feature that is not supported and has no usable fallback
+    UnsupportedFeature(&'static str),
+    /// A pipeline is created with a viewport count of 0
+    NoViewport,
+    /// A requested value is above a limit of the device
+    LimitExceeded { limit: &'static str, requested: u32, max: u32 },
+    /// An alignment is not a power of two
+    InvalidAlignment { alignment: usize },
+    /// The formats of a raw image copy have different texel sizes or aspects
+    IncompatibleFormats { src: vk::Format, dst: vk::Format },
+    /// Two vertex attributes share bytes of the same vertex
+    OverlappingAttributes { location: u32, other: u32 },
+    /// The vertex stride is smaller than the attributes it holds
+    VertexStrideTooSmall { stride: u32, required: u32 },
+    /// The stride between indirect draw commands is unaligned or smaller than a command
+    InvalidIndirectStride { stride: u32, required: u32 },
+    /// A field of a buffer block, or an offset into a buffer, is not where the shader layout
+    /// or the Vulkan alignment rules expect it
+    InvalidLayout {
+        field: &'static str,
+        expected: usize,
+        actual: usize,
+    },
 }
 
 impl From<NulError> for VulkanError {
@@ -28,7 +60,13 @@ impl From<ash::LoadingError> for VulkanError {
 
 impl From<vk::Result> for VulkanError {
     fn from(e: vk::Result) -> Self {
-        VulkanError::VulkanError(e)
+        match e {
+            vk::Result::ERROR_OUT_OF_HOST_MEMORY | vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => {
+                VulkanError::OutOfMemory
+            }
+            vk::Result::ERROR_DEVICE_LOST => VulkanError::DeviceLost,
+            _ => VulkanError::VulkanError(e),
+        }
     }
 }
 
@@ -45,7 +83,58 @@ impl fmt::Display for VulkanError {
             Self::DeviceSelectionError => fmt::Display::fmt(
                 "Selected device not available",
                 f),
-            Self::VulkanError(e) => fmt::Display::fmt(e, f)
+            Self::VulkanError(e) => fmt::Display::fmt(e, f),
+            Self::InvalidTextureData { expected, actual } => write!(
+                f,
+                "Texture data size mismatch, expected {} bytes but got {}",
+                expected, actual
+            ),
+            Self::OutOfMemory => fmt::Display::fmt("Out of host or device memory", f),
+            Self::DeviceLost => fmt::Display::fmt("The device was lost", f),
+            Self::UnsupportedTopology(topology) => {
+                write!(f, "Unsupported pipeline topology {:?}", topology)
+            }
+            Self::UnsupportedFeature(feature) => {
+                write!(f, "The {} feature is not supported by the device", feature)
+            }
+            Self::NoViewport => fmt::Display::fmt("A pipeline needs at least one viewport", f),
+            Self::LimitExceeded {
+                limit,
+                requested,
+                max,
+            } => write!(f, "Exceeded {} (got {}, limit {})", limit, requested, max),
+            Self::InvalidAlignment { alignment } => {
+                write!(f, "Alignment {} is not a power of two", alignment)
+            }
+            Self::IncompatibleFormats { src, dst } => write!(
+                f,
+                "Cannot copy {:?} texels into a {:?} image",
+                src, dst
+            ),
+            Self::OverlappingAttributes { location, other } => write!(
+                f,
+                "Vertex attributes at locations {} and {} overlap",
+                location, other
+            ),
+            Self::VertexStrideTooSmall { stride, required } => write!(
+                f,
+                "Vertex stride of {} bytes is smaller than the {} bytes of its attributes",
+                stride, required
+            ),
+            Self::InvalidIndirectStride { stride, required } => write!(
+                f,
+                "Indirect draw stride of {} bytes must be a multiple of 4 and at least {} bytes",
+                stride, required
+            ),
+            Self::InvalidLayout {
+                field,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Layout mismatch for {}, expected offset {} but got {}",
+                field, expected, actual
+            ),
         }
     }
 }
@@ -57,6 +146,65 @@ impl fmt::Debug for VulkanError {
             Self::VulkanError(arg0) => f.debug_tuple("VulkanError").field(arg0).finish(),
             Self::DeviceSelectionError => write!(f, "DeviceSelectionError"),
             Self::StringError => write!(f, "There was an error converting a string to a null terminated CString"),
+            Self::InvalidTextureData { expected, actual } => f
+                .debug_struct("InvalidTextureData")
+                .field("expected", expected)
+                .field("actual", actual)
+                .finish(),
+            Self::OutOfMemory => write!(f, "OutOfMemory"),
+            Self::DeviceLost => write!(f, "DeviceLost"),
+            Self::UnsupportedTopology(topology) => f
+                .debug_tuple("UnsupportedTopology")
+                .field(topology)
+                .finish(),
+            Self::UnsupportedFeature(feature) => {
+                f.debug_tuple("UnsupportedFeature").field(feature).finish()
+            }
+            Self::NoViewport => write!(f, "NoViewport"),
+            Self::LimitExceeded {
+                limit,
+                requested,
+                max,
+            } => f
+                .debug_struct("LimitExceeded")
+                .field("limit", limit)
+                .field("requested", requested)
+                .field("max", max)
+                .finish(),
+            Self::InvalidAlignment { alignment } => f
+                .debug_struct("InvalidAlignment")
+                .field("alignment", alignment)
+                .finish(),
+            Self::IncompatibleFormats { src, dst } => f
+                .debug_struct("IncompatibleFormats")
+                .field("src", src)
+                .field("dst", dst)
+                .finish(),
+            Self::OverlappingAttributes { location, other } => f
+                .debug_struct("OverlappingAttributes")
+                .field("location", location)
+                .field("other", other)
+                .finish(),
+            Self::VertexStrideTooSmall { stride, required } => f
+                .debug_struct("VertexStrideTooSmall")
+                .field("stride", stride)
+                .field("required", required)
+                .finish(),
+            Self::InvalidIndirectStride { stride, required } => f
+                .debug_struct("InvalidIndirectStride")
+                .field("stride", stride)
+                .field("required", required)
+                .finish(),
+            Self::InvalidLayout {
+                field,
+                expected,
+                actual,
+            } => f
+                .debug_struct("InvalidLayout")
+                .field("field", field)
+                .field("expected", expected)
+                .field("actual", actual)
+                .finish(),
         }
     }
 }
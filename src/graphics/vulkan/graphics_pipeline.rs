@@ -5,8 +5,233 @@
 use std::rc::Rc;
 
 use ash::vk;
+use nalgebra_glm as glm;
 
+use super::command_buffer::ViewportOrigin;
+use super::debug::Labeled;
 use super::device::VulkanDevice;
+use super::errors::VulkanError;
+use super::vertex::VertexInput;
+use crate::geometry::primitives::{Mesh, Winding};
+
+/// Blend equation of a color attachment, `src` is the fragment output and `dst` the stored color
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BlendState {
+    pub src_color: vk::BlendFactor,
+    pub dst_color: vk::BlendFactor,
+    pub color_op: vk::BlendOp,
+    pub src_alpha: vk::BlendFactor,
+    pub dst_alpha: vk::BlendFactor,
+    pub alpha_op: vk::BlendOp,
+}
+
+impl BlendState {
+    /// Straight alpha blending, `src * src_alpha + dst * (1 - src_alpha)`
+    pub const ALPHA: Self = Self {
+        src_color: vk::BlendFactor::SRC_ALPHA,
+        dst_color: vk::BlendFactor::ONE_MINUS_SRC_ALPHA,
+        color_op: vk::BlendOp::ADD,
+        src_alpha: vk::BlendFactor::ONE,
+        dst_alpha: vk::BlendFactor::ONE_MINUS_SRC_ALPHA,
+        alpha_op: vk::BlendOp::ADD,
+    };
+
+    /// Additive blending for lights and particles
+    pub const ADDITIVE: Self = Self {
+        src_color: vk::BlendFactor::ONE,
+        dst_color: vk::BlendFactor::ONE,
+        color_op: vk::BlendOp::ADD,
+        src_alpha: vk::BlendFactor::ONE,
+        dst_alpha: vk::BlendFactor::ONE,
+        alpha_op: vk::BlendOp::ADD,
+    };
+}
+
+/// Fixed function state baked into a graphics pipeline
+#[derive(Clone, Debug)]
+pub struct PipelineState {
+    pub vertex_input: VertexInput,
+    /// `PATCH_LIST` is rejected, pipelines don't take tessellation stages yet
+    pub topology: vk::PrimitiveTopology,
+    /// Allows to break strips and fans with the maximum index value (0xFFFF or 0xFFFFFFFF),
+    /// ignored with a warning for list topologies
+    pub primitive_restart: bool,
+    /// `LINE` and `POINT` rasterize the triangle edges or vertices only, they require the
+    /// `fill_mode_non_solid` feature and fail pipeline creation without it
+    pub polygon_mode: vk::PolygonMode,
+    /// Width in pixels of rasterized lines, other than 1.0 requires the `wide_lines` feature
+    pub line_width: f32,
+    pub cull_mode: vk::CullModeFlags,
+    /// Winding of the front facing triangles in framebuffer space
+    pub front_face: vk::FrontFace,
+    /// Color channels written to the color attachment, use an empty mask for a depth only pass
+    pub color_write_mask: vk::ColorComponentFlags,
+    /// Minimum fraction of samples shaded individually, clamped to `0.0..=1.0`,
+    /// requires the `sample_rate_shading` feature
+    pub sample_shading: Option<f32>,
+    /// Coverage mask ANDed with the rasterized samples
+    pub sample_mask: Option<u32>,
+    /// Number of viewports and scissors, at least one, more than one requires the
+    /// `multi_viewport` feature
+    ///
+    /// The extra viewports can't be selected yet, there is no geometry stage and the
+    /// `geometry_shader` feature is not enabled, so primitives all go to viewport 0
+    pub viewport_count: u32,
+    /// Clamps fragment depth instead of clipping against the near and far planes,
+    /// requires the `depth_clamp` feature
+    pub depth_clamp: bool,
+    /// Constant and slope scaled depth bias, used against shadow acne and decal z-fighting
+    pub depth_bias: Option<(f32, f32)>,
+    /// Bitwise operation applied between fragment and attachment colors instead of blending,
+    /// requires the `logic_op` feature
+    pub logic_op: Option<vk::LogicOp>,
+    /// Depth test compare operation, `GREATER` for a reversed Z depth buffer, None disables the test
+    pub depth_test: Option<vk::CompareOp>,
+    pub depth_write: bool,
+    /// Blending of each color attachment, None writes the fragment output unchanged.
+    /// Different states per attachment require the `independent_blend` feature,
+    /// an empty list disables blending on every attachment.
+    pub blend: Vec<Option<BlendState>>,
+}
+
+impl Default for PipelineState {
+    fn default() -> Self {
+        Self {
+            vertex_input: VertexInput::default(),
+            topology: vk::PrimitiveTopology::TRIANGLE_LIST,
+            primitive_restart: false,
+            polygon_mode: vk::PolygonMode::FILL,
+            line_width: 1.0,
+            cull_mode: vk::CullModeFlags::BACK,
+            front_face: vk::FrontFace::CLOCKWISE,
+            color_write_mask: vk::ColorComponentFlags::RGBA,
+            sample_shading: None,
+            sample_mask: None,
+            viewport_count: 1,
+            depth_clamp: false,
+            depth_bias: None,
+            logic_op: None,
+            depth_test: None,
+            depth_write: false,
+            blend: Vec::new(),
+        }
+    }
+}
+
+impl PipelineState {
+    /// State drawing the edges of a mesh over the same mesh drawn with `self`
+    ///
+    /// The edges are biased toward the viewer to win the depth test against the filled
+    /// triangles and don't write depth, the fragment shader outputs the edge color.
+    /// Creating the pipeline fails on devices without the `fill_mode_non_solid` feature.
+    pub fn wireframe_overlay(&self) -> Self {
+        let toward_viewer = match self.depth_test {
+            Some(vk::CompareOp::GREATER | vk::CompareOp::GREATER_OR_EQUAL) => 1.0,
+            _ => -1.0,
+        };
+        Self {
+            polygon_mode: vk::PolygonMode::LINE,
+            depth_bias: Some((toward_viewer, toward_viewer)),
+            depth_write: false,
+            ..self.clone()
+        }
+    }
+
+    /// State of the hull pass of an inverted hull outline derived from the mesh pass state
+    ///
+    /// The hull pass draws the mesh extruded along its normals in the vertex shader with a
+    /// solid color fragment shader, keeping only the back faces so that the mesh drawn
+    /// afterwards with `self` covers everything but a silhouette border.
+    pub fn inverted_hull(&self) -> Self {
+        let cull_mode = if self.cull_mode == vk::CullModeFlags::FRONT {
+            vk::CullModeFlags::BACK
+        } else {
+            vk::CullModeFlags::FRONT
+        };
+        Self {
+            cull_mode,
+            ..self.clone()
+        }
+    }
+
+    /// Load time diagnostic warning when the winding of `mesh` disagrees with `front_face`
+    ///
+    /// A mesh drawn with the wrong winding is culled away and looks invisible or inside-out.
+    /// The mesh winding is measured in object space and carried to the framebuffer through
+    /// `view_projection` and the viewport `origin`: a `TopLeft` viewport flips the Y of GL
+    /// style projections, so counter-clockwise meshes face `CLOCKWISE` there.
+    pub fn check_winding(&self, mesh: &Mesh, view_projection: &glm::Mat4, origin: ViewportOrigin) {
+        let Some(winding) = mesh.dominant_winding() else {
+            return;
+        };
+        let front_face = match self.framebuffer_winding(winding, view_projection, origin) {
+            Winding::CounterClockwise => vk::FrontFace::COUNTER_CLOCKWISE,
+            Winding::Clockwise => vk::FrontFace::CLOCKWISE,
+        };
+        if front_face == self.front_face {
+            return;
+        }
+        if self.cull_mode == vk::CullModeFlags::BACK {
+            log::warn!(
+                "Mesh faces {:?} on screen but the pipeline front face is {:?}, its outside will be culled",
+                front_face,
+                self.front_face
+            );
+        } else if self.cull_mode == vk::CullModeFlags::FRONT {
+            log::warn!(
+                "Mesh faces {:?} on screen but the pipeline front face is {:?}, its inside will be culled instead of its outside",
+                front_face,
+                self.front_face
+            );
+        }
+    }
+
+    fn framebuffer_winding(
+        &self,
+        winding: Winding,
+        view_projection: &glm::Mat4,
+        origin: ViewportOrigin,
+    ) -> Winding {
+        let reversed_depth = matches!(
+            self.depth_test,
+            Some(vk::CompareOp::GREATER | vk::CompareOp::GREATER_OR_EQUAL)
+        );
+        let flipped =
+            is_mirrored(view_projection, reversed_depth) != (origin == ViewportOrigin::TopLeft);
+        match (winding, flipped) {
+            (w, false) => w,
+            (Winding::Clockwise, true) => Winding::CounterClockwise,
+            (Winding::CounterClockwise, true) => Winding::Clockwise,
+        }
+    }
+}
+
+/// Whether `view_projection` mirrors the winding compared to a GL style projection
+///
+/// The perspective divide keeps the handedness of the x, y and w rows, so for perspective
+/// projections their determinant gives the answer whatever the depth convention.
+/// Orthographic projections have a constant w row and fall back on the full determinant,
+/// whose sign also depends on the depth direction.
+fn is_mirrored(view_projection: &glm::Mat4, reversed_depth: bool) -> bool {
+    let m = view_projection;
+    let xyw = glm::mat3(
+        m[(0, 0)],
+        m[(0, 1)],
+        m[(0, 2)],
+        m[(1, 0)],
+        m[(1, 1)],
+        m[(1, 2)],
+        m[(3, 0)],
+        m[(3, 1)],
+        m[(3, 2)],
+    );
+    let det = xyw.determinant();
+    if det.abs() > f32::EPSILON {
+        det > 0.0
+    } else {
+        (m.determinant() > 0.0) != reversed_depth
+    }
+}
 
 pub struct VulkanGraphicsPipeline {
     device: Rc<VulkanDevice>,
@@ -35,6 +260,14 @@ impl VulkanGraphicsPipeline {
     }
 }
 
+impl Labeled for VulkanGraphicsPipeline {
+    fn set_label(&self, label: &str) -> Result<(), VulkanError> {
+        self.device.set_object_name(self.pipeline, label)?;
+        self.device.set_object_name(self.layout, label)?;
+        self.device.set_object_name(self.render_pass, label)
+    }
+}
+
 impl Drop for VulkanGraphicsPipeline {
     fn drop(&mut self) {
         unsafe {
@@ -50,3 +283,67 @@ impl Drop for VulkanGraphicsPipeline {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::algorithms;
+
+    #[test]
+    fn gl_projection_flips_on_top_left_viewport() {
+        let state = PipelineState::default();
+        let projection = glm::perspective_rh_zo(1.0, 1.0, 0.1, 100.0);
+        let winding = state.framebuffer_winding(
+            Winding::CounterClockwise,
+            &projection,
+            ViewportOrigin::TopLeft,
+        );
+        assert_eq!(winding, Winding::Clockwise);
+        let winding = state.framebuffer_winding(
+            Winding::CounterClockwise,
+            &projection,
+            ViewportOrigin::BottomLeft,
+        );
+        assert_eq!(winding, Winding::CounterClockwise);
+    }
+
+    #[test]
+    fn reverse_z_projection_keeps_winding_convention() {
+        let state = PipelineState {
+            depth_test: Some(vk::CompareOp::GREATER),
+            ..Default::default()
+        };
+        let projection = algorithms::perspective_reverse_z(1.0, 1.0, 0.1);
+        let winding = state.framebuffer_winding(
+            Winding::CounterClockwise,
+            &projection,
+            ViewportOrigin::TopLeft,
+        );
+        assert_eq!(winding, Winding::Clockwise);
+    }
+
+    #[test]
+    fn y_flipped_projection_is_mirrored() {
+        let state = PipelineState::default();
+        let mut projection = glm::perspective_rh_zo(1.0, 1.0, 0.1, 100.0);
+        projection[(1, 1)] = -projection[(1, 1)];
+        let winding = state.framebuffer_winding(
+            Winding::CounterClockwise,
+            &projection,
+            ViewportOrigin::TopLeft,
+        );
+        assert_eq!(winding, Winding::CounterClockwise);
+    }
+
+    #[test]
+    fn orthographic_projection_is_not_mirrored() {
+        let state = PipelineState::default();
+        let projection = glm::ortho_rh_zo(-1.0, 1.0, -1.0, 1.0, 0.1, 100.0);
+        let winding = state.framebuffer_winding(
+            Winding::CounterClockwise,
+            &projection,
+            ViewportOrigin::BottomLeft,
+        );
+        assert_eq!(winding, Winding::CounterClockwise);
+    }
+}
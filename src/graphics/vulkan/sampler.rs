@@ -0,0 +1,156 @@
+/*
+* SPDX-License-Identifier: MIT
+*/
+
+use std::rc::Rc;
+
+use ash::vk;
+
+use super::debug::Labeled;
+use super::device::VulkanDevice;
+use super::errors::VulkanError;
+
+/// Texture filtering quality, expanded into the min, mag and mipmap filters of a sampler
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Filtering {
+    Nearest,
+    Bilinear,
+    /// Bilinear filtering blended between mip levels, the texture needs a full mip chain
+    Trilinear,
+    /// Trilinear filtering with the given maximum anisotropy, requires the `sampler_anisotropy`
+    /// feature and falls back to trilinear without it
+    Anisotropic(f32),
+}
+
+impl Filtering {
+    /// Returns true when the sampled texture is expected to have mipmaps
+    pub fn uses_mipmaps(&self) -> bool {
+        matches!(self, Self::Trilinear | Self::Anisotropic(_))
+    }
+
+    pub(crate) fn apply<'a>(
+        &self,
+        create_info: vk::SamplerCreateInfo<'a>,
+        device: &VulkanDevice,
+    ) -> vk::SamplerCreateInfo<'a> {
+        let (filter, mipmap_mode) = match self {
+            Self::Nearest => (vk::Filter::NEAREST, vk::SamplerMipmapMode::NEAREST),
+            _ => (vk::Filter::LINEAR, vk::SamplerMipmapMode::LINEAR),
+        };
+
+        let create_info = create_info
+            .mag_filter(filter)
+            .min_filter(filter)
+            .mipmap_mode(mipmap_mode)
+            .max_lod(if self.uses_mipmaps() {
+                vk::LOD_CLAMP_NONE
+            } else {
+                0.0
+            });
+
+        match self {
+            Self::Anisotropic(_) if device.features().sampler_anisotropy != vk::TRUE => {
+                log::warn!("Anisotropic filtering is not supported by the device, using trilinear");
+                create_info
+            }
+            Self::Anisotropic(max_anisotropy) => create_info
+                .anisotropy_enable(true)
+                .max_anisotropy(max_anisotropy.clamp(1.0, device.limits().max_sampler_anisotropy)),
+            _ => create_info,
+        }
+    }
+}
+
+/// Sampling parameters baked into a sampler
+///
+/// Samplers are independent from textures, one texture can be sampled with several of them
+/// and one sampler serves any number of textures.
+#[derive(Clone, Debug)]
+pub struct SamplerState {
+    pub filtering: Filtering,
+    /// Wrapping of the u, v and w texture coordinates outside `0.0..1.0`
+    pub address_mode: [vk::SamplerAddressMode; 3],
+    /// Color read outside the texture with the `CLAMP_TO_BORDER` address mode
+    pub border_color: vk::BorderColor,
+    /// Compares the sampled depth against a reference instead of returning it, used with
+    /// `SampleCmp` for hardware filtered shadow maps
+    pub compare: Option<vk::CompareOp>,
+    /// Offset added to the computed mip level, positive values blur and negative ones sharpen.
+    /// Clamped to the `max_sampler_lod_bias` device limit
+    pub lod_bias: f32,
+    /// Range of mip levels that can be sampled, a streaming texture clamps out the levels
+    /// not loaded yet. None allows every level when the filtering uses mipmaps
+    pub lod_range: Option<(f32, f32)>,
+}
+
+impl Default for SamplerState {
+    fn default() -> Self {
+        Self {
+            filtering: Filtering::Trilinear,
+            address_mode: [vk::SamplerAddressMode::REPEAT; 3],
+            border_color: vk::BorderColor::FLOAT_TRANSPARENT_BLACK,
+            compare: None,
+            lod_bias: 0.0,
+            lod_range: None,
+        }
+    }
+}
+
+impl SamplerState {
+    pub fn new(filtering: Filtering) -> Self {
+        Self {
+            filtering,
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn create_info(&self, device: &VulkanDevice) -> vk::SamplerCreateInfo<'static> {
+        let max_lod_bias = device.limits().max_sampler_lod_bias;
+        let mut create_info = self.filtering.apply(
+            vk::SamplerCreateInfo::default()
+                .address_mode_u(self.address_mode[0])
+                .address_mode_v(self.address_mode[1])
+                .address_mode_w(self.address_mode[2])
+                .border_color(self.border_color)
+                .compare_enable(self.compare.is_some())
+                .compare_op(self.compare.unwrap_or(vk::CompareOp::ALWAYS))
+                .mip_lod_bias(self.lod_bias.clamp(-max_lod_bias, max_lod_bias)),
+            device,
+        );
+        if let Some((min_lod, max_lod)) = self.lod_range {
+            create_info = create_info.min_lod(min_lod).max_lod(max_lod.max(min_lod));
+        }
+        create_info
+    }
+}
+
+pub struct VulkanSampler {
+    device: Rc<VulkanDevice>,
+    sampler: vk::Sampler,
+}
+
+impl VulkanSampler {
+    pub(crate) fn new(device: Rc<VulkanDevice>, sampler: vk::Sampler) -> Self {
+        Self { device, sampler }
+    }
+
+    pub fn sampler(&self) -> vk::Sampler {
+        self.sampler
+    }
+}
+
+impl Labeled for VulkanSampler {
+    fn set_label(&self, label: &str) -> Result<(), VulkanError> {
+        self.device.set_object_name(self.sampler, label)
+    }
+}
+
+impl Drop for VulkanSampler {
+    fn drop(&mut self) {
+        unsafe {
+            self.device
+                .logical_device()
+                .destroy_sampler(self.sampler, None);
+        }
+    }
+}
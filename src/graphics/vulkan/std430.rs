@@ -0,0 +1,157 @@
+/*
+* SPDX-License-Identifier: MIT
+*/
+
+use super::errors::VulkanError;
+use super::utils::align_up;
+
+/// GLSL/HLSL member types of a `std430` storage buffer block
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Std430Type {
+    /// `float`, `int`, `uint`
+    Scalar,
+    Vec2,
+    Vec3,
+    Vec4,
+    /// Three `vec4` aligned columns
+    Mat3,
+    Mat4,
+    /// Array of `count` elements, the stride is the element size rounded to its alignment
+    Array(&'static Std430Type, usize),
+}
+
+impl Std430Type {
+    pub fn alignment(&self) -> usize {
+        match self {
+            Self::Scalar => 4,
+            Self::Vec2 => 8,
+            Self::Vec3 | Self::Vec4 | Self::Mat3 | Self::Mat4 => 16,
+            Self::Array(element, _) => element.alignment(),
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        match self {
+            Self::Scalar => 4,
+            Self::Vec2 => 8,
+            Self::Vec3 => 12,
+            Self::Vec4 => 16,
+            Self::Mat3 => 48,
+            Self::Mat4 => 64,
+            Self::Array(element, count) => align_up(element.size(), element.alignment()) * count,
+        }
+    }
+}
+
+/// A member of a Rust struct mirroring a `std430` block, the offset usually comes from
+/// `std::mem::offset_of!`
+#[derive(Clone, Copy, Debug)]
+pub struct Std430Field {
+    pub name: &'static str,
+    pub ty: Std430Type,
+    pub offset: usize,
+}
+
+impl Std430Field {
+    pub fn new(name: &'static str, ty: Std430Type, offset: usize) -> Self {
+        Self { name, ty, offset }
+    }
+}
+
+/// Checks that `fields`, in declaration order, sit at their `std430` offsets and that
+/// `struct_size` is the padded size of the block so that arrays of it keep the layout
+///
+/// A mismatch does not fail the upload, the shader silently reads shifted data, so call
+/// this once per type in debug builds before filling a storage buffer.
+pub fn validate_std430(fields: &[Std430Field], struct_size: usize) -> Result<(), VulkanError> {
+    let mut offset = 0;
+    let mut struct_alignment = Std430Type::Scalar.alignment();
+    for field in fields {
+        let expected = align_up(offset, field.ty.alignment());
+        if field.offset != expected {
+            return Err(VulkanError::InvalidLayout {
+                field: field.name,
+                expected,
+                actual: field.offset,
+            });
+        }
+        offset = expected + field.ty.size();
+        struct_alignment = struct_alignment.max(field.ty.alignment());
+    }
+
+    let expected = align_up(offset, struct_alignment);
+    if struct_size != expected {
+        return Err(VulkanError::InvalidLayout {
+            field: "struct size",
+            expected,
+            actual: struct_size,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::mem::{offset_of, size_of};
+
+    use super::*;
+
+    #[repr(C)]
+    struct Light {
+        position: [f32; 3],
+        intensity: f32,
+        color: [f32; 4],
+        weights: [f32; 3],
+    }
+
+    #[test]
+    fn validate_std430_packs_vec3_and_pads_struct() {
+        let fields = [
+            Std430Field::new("position", Std430Type::Vec3, offset_of!(Light, position)),
+            Std430Field::new(
+                "intensity",
+                Std430Type::Scalar,
+                offset_of!(Light, intensity),
+            ),
+            Std430Field::new("color", Std430Type::Vec4, offset_of!(Light, color)),
+            Std430Field::new(
+                "weights",
+                Std430Type::Array(&Std430Type::Scalar, 3),
+                offset_of!(Light, weights),
+            ),
+        ];
+        // the block is padded to 48 bytes but the Rust struct is 44 bytes long
+        assert!(matches!(
+            validate_std430(&fields, size_of::<Light>()),
+            Err(VulkanError::InvalidLayout {
+                field: "struct size",
+                expected: 48,
+                actual: 44,
+            })
+        ));
+        assert!(validate_std430(&fields, 48).is_ok());
+    }
+
+    #[test]
+    fn validate_std430_reports_misaligned_field() {
+        let fields = [
+            Std430Field::new("a", Std430Type::Vec3, 0),
+            Std430Field::new("b", Std430Type::Vec3, 12),
+        ];
+        assert!(matches!(
+            validate_std430(&fields, 32),
+            Err(VulkanError::InvalidLayout {
+                field: "b",
+                expected: 16,
+                actual: 12,
+            })
+        ));
+    }
+
+    #[test]
+    fn std430_arrays_round_the_element_stride() {
+        assert_eq!(Std430Type::Array(&Std430Type::Scalar, 4).size(), 16);
+        assert_eq!(Std430Type::Array(&Std430Type::Vec3, 2).size(), 32);
+        assert_eq!(Std430Type::Array(&Std430Type::Vec3, 2).alignment(), 16);
+    }
+}
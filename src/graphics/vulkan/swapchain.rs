@@ -8,7 +8,7 @@ use ash::{khr, vk};
 
 use crate::graphics::vulkan::utils;
 
-use super::{device, errors::VulkanError, graphics_pipeline::VulkanGraphicsPipeline};
+use super::{debug::Labeled, device, errors::VulkanError, graphics_pipeline::VulkanGraphicsPipeline};
 
 pub struct VulkanSwapChain {
     device: Rc<device::VulkanDevice>,
@@ -26,11 +26,12 @@ impl VulkanSwapChain {
         device: Rc<device::VulkanDevice>,
         width: u32,
         height: u32,
+        srgb: bool,
         image_sharing_mode: vk::SharingMode,
         queue_family_indices: &[u32],
     ) -> Self {
         let swapchain_support = device.query_swapchain_support();
-        let surface_format = swapchain_support.choose_swapchain_format();
+        let surface_format = swapchain_support.choose_swapchain_format(srgb);
         let present_mode = swapchain_support.choose_swapchain_present_mode();
         let extent = swapchain_support.choose_swapchain_extent(width, height);
 
@@ -94,6 +95,18 @@ impl VulkanSwapChain {
         }
     }
 
+    /// Returns the whole area of the swapchain images, to pass to
+    /// `VulkanCommandBuffer::set_viewport` like the area of any other render target
+    pub fn render_area(&self) -> vk::Rect2D {
+        vk::Rect2D::default().extent(self.extent)
+    }
+
+    /// Returns the framebuffer drawing to the swapchain image `image_index`,
+    /// available once `create_framebuffers` has been called
+    pub fn framebuffer(&self, image_index: u32) -> Option<vk::Framebuffer> {
+        self.framebuffers.get(image_index as usize).copied()
+    }
+
     pub fn create_framebuffers(
         &mut self,
         pipeline: VulkanGraphicsPipeline,
@@ -121,6 +134,16 @@ impl VulkanSwapChain {
     }
 }
 
+impl Labeled for VulkanSwapChain {
+    fn set_label(&self, label: &str) -> Result<(), VulkanError> {
+        self.device.set_object_name(self.swapchain, label)?;
+        for image in &self.images {
+            self.device.set_object_name(*image, label)?;
+        }
+        Ok(())
+    }
+}
+
 impl Drop for VulkanSwapChain {
     fn drop(&mut self) {
         unsafe {
@@ -147,9 +170,15 @@ pub(crate) struct SwapChainSupportDetail {
 }
 
 impl SwapChainSupportDetail {
-    fn choose_swapchain_format(&self) -> vk::SurfaceFormatKHR {
+    /// An sRGB format gamma encodes the linear shader output on write, a UNORM one stores it as is
+    fn choose_swapchain_format(&self, srgb: bool) -> vk::SurfaceFormatKHR {
+        let preferred_format = if srgb {
+            vk::Format::B8G8R8A8_SRGB
+        } else {
+            vk::Format::B8G8R8A8_UNORM
+        };
         for available_format in &self.formats {
-            if available_format.format == vk::Format::B8G8R8A8_SRGB
+            if available_format.format == preferred_format
                 && available_format.color_space == vk::ColorSpaceKHR::SRGB_NONLINEAR
             {
                 return *available_format;
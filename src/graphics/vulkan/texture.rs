@@ -0,0 +1,202 @@
+/*
+* SPDX-License-Identifier: MIT
+*/
+
+use ash::vk;
+
+use super::errors::VulkanError;
+use super::utils::align_up;
+
+/// Pixel formats usable for textures and render targets
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextureFormat {
+    R8,
+    RG8,
+    RGBA8,
+    RGBA8Srgb,
+    RGBA16F,
+    RGBA32F,
+    R11G11B10F,
+    R32UI,
+    RG32UI,
+    RGBA32UI,
+    R32I,
+    RGBA32I,
+    Depth32F,
+    Depth24Stencil8,
+}
+
+impl TextureFormat {
+    pub fn vk_format(&self) -> vk::Format {
+        match self {
+            Self::R8 => vk::Format::R8_UNORM,
+            Self::RG8 => vk::Format::R8G8_UNORM,
+            Self::RGBA8 => vk::Format::R8G8B8A8_UNORM,
+            Self::RGBA8Srgb => vk::Format::R8G8B8A8_SRGB,
+            Self::RGBA16F => vk::Format::R16G16B16A16_SFLOAT,
+            Self::RGBA32F => vk::Format::R32G32B32A32_SFLOAT,
+            Self::R11G11B10F => vk::Format::B10G11R11_UFLOAT_PACK32,
+            Self::R32UI => vk::Format::R32_UINT,
+            Self::RG32UI => vk::Format::R32G32_UINT,
+            Self::RGBA32UI => vk::Format::R32G32B32A32_UINT,
+            Self::R32I => vk::Format::R32_SINT,
+            Self::RGBA32I => vk::Format::R32G32B32A32_SINT,
+            Self::Depth32F => vk::Format::D32_SFLOAT,
+            Self::Depth24Stencil8 => vk::Format::D24_UNORM_S8_UINT,
+        }
+    }
+
+    pub fn bytes_per_pixel(&self) -> usize {
+        match self {
+            Self::R8 => 1,
+            Self::RG8 => 2,
+            Self::RGBA8 | Self::RGBA8Srgb => 4,
+            Self::RGBA16F => 8,
+            Self::RG32UI => 8,
+            Self::RGBA32F | Self::RGBA32UI | Self::RGBA32I => 16,
+            Self::R11G11B10F | Self::R32UI | Self::R32I => 4,
+            Self::Depth32F | Self::Depth24Stencil8 => 4,
+        }
+    }
+
+    /// Integer formats are read with `Texture2D<uint4>` / `Texture2D<int4>` in shaders
+    /// and can't be linearly filtered, sample them with `Filtering::Nearest`
+    pub fn is_integer(&self) -> bool {
+        matches!(
+            self,
+            Self::R32UI | Self::RG32UI | Self::RGBA32UI | Self::R32I | Self::RGBA32I
+        )
+    }
+
+    pub fn aspect_mask(&self) -> vk::ImageAspectFlags {
+        match self {
+            Self::Depth32F => vk::ImageAspectFlags::DEPTH,
+            Self::Depth24Stencil8 => vk::ImageAspectFlags::DEPTH | vk::ImageAspectFlags::STENCIL,
+            _ => vk::ImageAspectFlags::COLOR,
+        }
+    }
+
+    /// Raw image copies require formats with the same texel size and aspects,
+    /// `R32UI` and `RGBA8` can be copied into each other but not `RGBA8` and `Depth32F`
+    pub fn is_copy_compatible(&self, other: TextureFormat) -> bool {
+        self.bytes_per_pixel() == other.bytes_per_pixel()
+            && self.aspect_mask() == other.aspect_mask()
+    }
+
+    /// Checks that `data` holds exactly `width` x `height` pixels of this format
+    pub fn validate_data(&self, width: u32, height: u32, data: &[u8]) -> Result<(), VulkanError> {
+        let expected = width as usize * height as usize * self.bytes_per_pixel();
+        if data.len() == expected {
+            Ok(())
+        } else {
+            Err(VulkanError::InvalidTextureData {
+                expected,
+                actual: data.len(),
+            })
+        }
+    }
+}
+
+/// Flips the rows of tightly packed pixel data in place
+///
+/// Vulkan and image decoders both put the first row at the top, so this is only needed for
+/// assets authored with bottom-left origin texture coordinates.
+pub fn flip_y(format: TextureFormat, width: u32, data: &mut [u8]) {
+    let row_size = width as usize * format.bytes_per_pixel();
+    if row_size == 0 {
+        return;
+    }
+    let rows = data.len() / row_size;
+    for row in 0..rows / 2 {
+        let (top, bottom) = data.split_at_mut((rows - row - 1) * row_size);
+        top[row * row_size..(row + 1) * row_size].swap_with_slice(&mut bottom[..row_size]);
+    }
+}
+
+/// Expands 3 channel RGB pixels to tightly packed `RGBA8` with an opaque alpha
+///
+/// Three byte formats are rarely supported for sampled images, so RGB data is widened before
+/// upload. Source rows are padded to `row_alignment` bytes, a power of two that is 1 for tightly
+/// packed data and 4 for data laid out for the OpenGL default unpack alignment, getting it wrong
+/// skews the image.
+pub fn expand_rgb(
+    width: u32,
+    height: u32,
+    row_alignment: usize,
+    data: &[u8],
+) -> Result<Vec<u8>, VulkanError> {
+    if !row_alignment.is_power_of_two() {
+        return Err(VulkanError::InvalidAlignment {
+            alignment: row_alignment,
+        });
+    }
+    let row_size = width as usize * 3;
+    let row_pitch = align_up(row_size, row_alignment);
+    // the last row doesn't need its padding
+    let expected = match height as usize {
+        0 => 0,
+        height => row_pitch * (height - 1) + row_size,
+    };
+    if data.len() < expected {
+        return Err(VulkanError::InvalidTextureData {
+            expected,
+            actual: data.len(),
+        });
+    }
+
+    let mut pixels = Vec::with_capacity(width as usize * height as usize * 4);
+    for row in 0..height as usize {
+        let start = row * row_pitch;
+        for rgb in data[start..start + row_size].chunks_exact(3) {
+            pixels.extend_from_slice(&[rgb[0], rgb[1], rgb[2], u8::MAX]);
+        }
+    }
+    Ok(pixels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expand_rgb_skips_row_padding() {
+        let data = [1, 2, 3, 4, 5, 6, 0, 0, 7, 8, 9, 10, 11, 12];
+        let pixels = expand_rgb(2, 2, 4, &data).unwrap();
+        assert_eq!(
+            pixels,
+            [1, 2, 3, 255, 4, 5, 6, 255, 7, 8, 9, 255, 10, 11, 12, 255]
+        );
+    }
+
+    #[test]
+    fn expand_rgb_tightly_packed() {
+        let data = [1, 2, 3, 4, 5, 6];
+        let pixels = expand_rgb(1, 2, 1, &data).unwrap();
+        assert_eq!(pixels, [1, 2, 3, 255, 4, 5, 6, 255]);
+    }
+
+    #[test]
+    fn expand_rgb_rejects_non_power_of_two_alignment() {
+        let data = [0; 16];
+        assert!(matches!(
+            expand_rgb(2, 2, 3, &data),
+            Err(VulkanError::InvalidAlignment { alignment: 3 })
+        ));
+        assert!(matches!(
+            expand_rgb(2, 2, 0, &data),
+            Err(VulkanError::InvalidAlignment { alignment: 0 })
+        ));
+    }
+
+    #[test]
+    fn expand_rgb_rejects_short_data() {
+        let data = [0; 7];
+        assert!(matches!(
+            expand_rgb(2, 2, 1, &data),
+            Err(VulkanError::InvalidTextureData {
+                expected: 12,
+                actual: 7
+            })
+        ));
+    }
+}
@@ -49,6 +49,15 @@ pub(crate) unsafe fn raw_to_string(raw_string_array: &[ffi::c_char]) -> String {
         .to_owned()
 }
 
+/// Rounds `value` up to the next multiple of `alignment`, a power of two as guaranteed by Vulkan limits
+pub(crate) fn align_up(value: usize, alignment: usize) -> usize {
+    if alignment == 0 {
+        value
+    } else {
+        (value + alignment - 1) & !(alignment - 1)
+    }
+}
+
 pub(crate) fn string_slice_to_raw_slice(vector: &[CString]) -> Vec<*const ffi::c_char> {
     vector
         .iter()
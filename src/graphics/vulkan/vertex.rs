@@ -0,0 +1,280 @@
+/*
+* SPDX-License-Identifier: MIT
+*/
+
+use ash::vk;
+
+use super::errors::VulkanError;
+use crate::geometry::primitives::{as_bytes, SimpleVertex, Vertex, VertexData, VertexFull};
+
+/// Describes one attribute of an interleaved vertex buffer
+#[derive(Clone, Copy, Debug)]
+pub struct VertexDesc {
+    /// Shader input location, `[[vk::location(n)]]` in HLSL
+    pub location: u32,
+    pub format: vk::Format,
+    /// Offset in bytes from the start of the vertex
+    pub offset: u32,
+    /// Size of the attribute in bytes
+    pub size: u32,
+    /// Fetched once per instance from the instance buffer (binding 1) instead of once per vertex
+    pub per_instance: bool,
+}
+
+impl VertexDesc {
+    pub fn new(location: u32, format: vk::Format, offset: u32, size: u32) -> Self {
+        Self {
+            location,
+            format,
+            offset,
+            size,
+            per_instance: false,
+        }
+    }
+
+    /// Describes `count` 32 bit floats starting `offset` floats into the vertex
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` is not between 1 and 4.
+    pub fn floats(location: u32, count: u32, offset: u32) -> Self {
+        let format = match count {
+            1 => vk::Format::R32_SFLOAT,
+            2 => vk::Format::R32G32_SFLOAT,
+            3 => vk::Format::R32G32B32_SFLOAT,
+            4 => vk::Format::R32G32B32A32_SFLOAT,
+            _ => panic!("A float vertex attribute has between 1 and 4 components, got {count}"),
+        };
+        let float_size = std::mem::size_of::<f32>() as u32;
+        Self::new(location, format, offset * float_size, count * float_size)
+    }
+
+    /// Describes `count` 32 bit unsigned integers starting `offset` integers into the vertex,
+    /// read as `uint` in shaders without conversion, like a texture array layer index
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` is not between 1 and 4.
+    pub fn uints(location: u32, count: u32, offset: u32) -> Self {
+        let format = match count {
+            1 => vk::Format::R32_UINT,
+            2 => vk::Format::R32G32_UINT,
+            3 => vk::Format::R32G32B32_UINT,
+            4 => vk::Format::R32G32B32A32_UINT,
+            _ => panic!("A uint vertex attribute has between 1 and 4 components, got {count}"),
+        };
+        let uint_size = std::mem::size_of::<u32>() as u32;
+        Self::new(location, format, offset * uint_size, count * uint_size)
+    }
+
+    /// Moves the attribute to the per instance buffer, its offset is then relative to the
+    /// start of the instance data
+    pub fn per_instance(self) -> Self {
+        Self {
+            per_instance: true,
+            ..self
+        }
+    }
+}
+
+/// Vertex buffer layout consumed by a pipeline
+#[derive(Clone, Debug, Default)]
+pub struct VertexInput {
+    /// Bytes between consecutive vertices, 0 for tightly packed attributes
+    pub stride: u32,
+    /// Bytes between consecutive instances of the instance buffer, 0 for tightly packed
+    /// per instance attributes. Padded instance structs must give their size
+    pub instance_stride: u32,
+    /// Attributes of the vertex and instance buffers, empty for pipelines generating their
+    /// vertices in the shader like the fullscreen triangle
+    pub attributes: Vec<VertexDesc>,
+}
+
+impl VertexInput {
+    pub fn new(stride: u32, attributes: Vec<VertexDesc>) -> Self {
+        Self {
+            stride,
+            instance_stride: 0,
+            attributes,
+        }
+    }
+
+    /// Adds the per instance attributes of a buffer of `T` instances
+    pub fn with_instances<T: VertexData>(mut self, attributes: &[VertexDesc]) -> Self {
+        self.instance_stride = std::mem::size_of::<T>() as u32;
+        self.attributes
+            .extend(attributes.iter().map(|attribute| attribute.per_instance()));
+        self
+    }
+
+    /// Layout of a buffer of `T` vertices
+    pub fn of<T: VertexLayout>() -> Self {
+        Self::new(std::mem::size_of::<T>() as u32, T::attributes())
+    }
+}
+
+/// Returns the stride of a tightly packed vertex made of `attributes`
+pub fn packed_stride(attributes: &[VertexDesc]) -> u32 {
+    attributes
+        .iter()
+        .map(|attribute| attribute.offset + attribute.size)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Checks that interleaved `attributes` don't overlap and fit in `stride` bytes
+///
+/// Overlapping attributes read each other's bytes, which shows up as garbage geometry
+/// rather than as an error. A `stride` of 0 stands for the packed stride and always fits.
+pub fn validate_attributes(attributes: &[VertexDesc], stride: u32) -> Result<(), VulkanError> {
+    let mut sorted = attributes.to_vec();
+    sorted.sort_by_key(|attribute| attribute.offset);
+    for pair in sorted.windows(2) {
+        if pair[0].offset + pair[0].size > pair[1].offset {
+            return Err(VulkanError::OverlappingAttributes {
+                location: pair[0].location,
+                other: pair[1].location,
+            });
+        }
+    }
+
+    let required = packed_stride(attributes);
+    if stride != 0 && stride < required {
+        return Err(VulkanError::VertexStrideTooSmall { stride, required });
+    }
+    Ok(())
+}
+
+/// One attribute stored in its own array, the input of [`interleave`]
+pub struct AttributeSource<'a> {
+    pub location: u32,
+    pub format: vk::Format,
+    /// Size in bytes of one element
+    pub size: u32,
+    pub data: &'a [u8],
+}
+
+impl<'a> AttributeSource<'a> {
+    /// An attribute made of one `T` per vertex, `format` must match the layout of `T`
+    pub fn new<T: VertexData>(location: u32, format: vk::Format, data: &'a [T]) -> Self {
+        Self {
+            location,
+            format,
+            size: std::mem::size_of::<T>() as u32,
+            data: as_bytes(data),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.data.len() / self.size as usize
+    }
+}
+
+/// Interleaves separate attribute arrays into one packed vertex buffer
+///
+/// Attributes are laid out in the order of `sources`, the returned descriptions go with a
+/// stride of 0 (packed) at pipeline creation.
+///
+/// # Panics
+///
+/// Panics if the sources don't hold the same number of vertices.
+pub fn interleave(sources: &[AttributeSource]) -> (Vec<u8>, Vec<VertexDesc>) {
+    let mut offset = 0;
+    let attributes: Vec<VertexDesc> = sources
+        .iter()
+        .map(|source| {
+            let attribute = VertexDesc::new(source.location, source.format, offset, source.size);
+            offset += source.size;
+            attribute
+        })
+        .collect();
+
+    let vertex_count = sources.first().map_or(0, AttributeSource::len);
+    assert!(
+        sources.iter().all(|source| source.len() == vertex_count),
+        "Interleaved attributes must have the same number of vertices"
+    );
+
+    let mut data = Vec::with_capacity(vertex_count * offset as usize);
+    for vertex in 0..vertex_count {
+        for source in sources {
+            let size = source.size as usize;
+            data.extend_from_slice(&source.data[vertex * size..(vertex + 1) * size]);
+        }
+    }
+    (data, attributes)
+}
+
+/// Vertex types with a known attribute layout
+///
+/// Attributes use the locations position = 0, normal = 1, uv = 2, uv2 = 3 and color = 4,
+/// shaders simply ignore the ones they don't declare.
+pub trait VertexLayout {
+    fn attributes() -> Vec<VertexDesc>;
+}
+
+impl VertexLayout for SimpleVertex {
+    fn attributes() -> Vec<VertexDesc> {
+        vec![VertexDesc::floats(0, 3, 0), VertexDesc::floats(1, 3, 3)]
+    }
+}
+
+impl VertexLayout for Vertex {
+    fn attributes() -> Vec<VertexDesc> {
+        vec![
+            VertexDesc::floats(0, 3, 0),
+            VertexDesc::floats(1, 3, 3),
+            VertexDesc::floats(2, 2, 6),
+        ]
+    }
+}
+
+impl VertexLayout for VertexFull {
+    fn attributes() -> Vec<VertexDesc> {
+        vec![
+            VertexDesc::floats(0, 3, 0),
+            VertexDesc::floats(1, 3, 3),
+            VertexDesc::floats(2, 2, 6),
+            VertexDesc::floats(3, 2, 8),
+            VertexDesc::floats(4, 4, 10),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_attributes_accepts_layouts() {
+        let attributes = Vertex::attributes();
+        assert!(validate_attributes(&attributes, 0).is_ok());
+        assert!(validate_attributes(&attributes, 32).is_ok());
+        // padding after the last attribute is allowed
+        assert!(validate_attributes(&attributes, 48).is_ok());
+    }
+
+    #[test]
+    fn validate_attributes_reports_overlap() {
+        // declared out of order, the position overlaps the normal starting 8 bytes in
+        let attributes = [VertexDesc::floats(1, 3, 2), VertexDesc::floats(0, 3, 0)];
+        assert!(matches!(
+            validate_attributes(&attributes, 0),
+            Err(VulkanError::OverlappingAttributes {
+                location: 0,
+                other: 1,
+            })
+        ));
+    }
+
+    #[test]
+    fn validate_attributes_reports_small_stride() {
+        assert!(matches!(
+            validate_attributes(&Vertex::attributes(), 24),
+            Err(VulkanError::VertexStrideTooSmall {
+                stride: 24,
+                required: 32,
+            })
+        ));
+    }
+}